@@ -0,0 +1,116 @@
+use std::env;
+use std::sync::Arc;
+
+use taskmaster::config::unix_http_server::unix_http_server;
+use taskmaster::config::{adapter::Adapter, runtimecontext::RuntimeContext};
+use taskmaster::control::client::Client;
+use taskmaster::control::connection::Address;
+use taskmaster::errors::ConfigParseError;
+use taskmaster::exec::command_loop::{command_loop, run_once, run_script};
+use taskmaster::exec::registry::{Format, Session};
+
+enum Mode {
+    Interactive,
+    Once(Vec<String>),
+    Script(String),
+}
+
+struct Options {
+    file_path: Option<String>,
+    socket: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    mode: Mode,
+    format: Format,
+}
+
+fn usage(s: &str) {
+    eprintln!(
+        "Usage: {} [-c <config_file_path>] [-s <socket> | -s http://<host>:<port>] [-u <username> -p <password>] [--json] [-f <script> | <command> [args...]]",
+        s
+    );
+    std::process::exit(1);
+}
+
+fn parse_args(args: &[String]) -> Option<Options> {
+    let mut file_path = None;
+    let mut socket = None;
+    let mut username = None;
+    let mut password = None;
+    let mut mode = Mode::Interactive;
+    let mut format = Format::Text;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => format = Format::Json,
+            "-c" if file_path.is_none() => file_path = Some(iter.next()?.clone()),
+            "-s" if socket.is_none() => socket = Some(iter.next()?.clone()),
+            "-u" if username.is_none() => username = Some(iter.next()?.clone()),
+            "-p" if password.is_none() => password = Some(iter.next()?.clone()),
+            "-f" if matches!(mode, Mode::Interactive) => mode = Mode::Script(iter.next()?.clone()),
+            _ if !arg.starts_with('-') && matches!(mode, Mode::Interactive) => {
+                mode = Mode::Once(std::iter::once(arg).chain(iter).cloned().collect());
+                break;
+            }
+            _ => return None,
+        }
+    }
+    if username.is_some() != password.is_some() {
+        return None;
+    }
+    Some(Options {
+        file_path,
+        socket,
+        username,
+        password,
+        mode,
+        format,
+    })
+}
+
+/// The socket or port given with `-s`, else the socket of the configuration file.
+fn address(options: &Options) -> Result<Address, ConfigParseError> {
+    if let Some(socket) = &options.socket {
+        return Ok(Address::parse(socket));
+    }
+    let mut runtime_context = RuntimeContext::default();
+    match Adapter::parse_client_config(&mut runtime_context, options.file_path.as_ref()) {
+        Ok(()) => Ok(Address::Unix(
+            runtime_context.config().unix_http_server().file().into(),
+        )),
+        // without any configuration, look where taskmasterd listens by default
+        Err(ConfigParseError::FileNotFound) if options.file_path.is_none() => {
+            Ok(Address::Unix(unix_http_server::DEFAULT_FILE.into()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    let Some(options) = parse_args(&args) else {
+        usage(&args[0]);
+        return;
+    };
+    let credentials = options.username.clone().zip(options.password.clone());
+    let client = match address(&options) {
+        Ok(address) => Arc::new(Client::new(address, credentials)),
+        Err(e) => {
+            eprintln!("Error parsing config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let session = Session::new(options.format, matches!(options.mode, Mode::Interactive));
+    let code = match options.mode {
+        Mode::Interactive => match command_loop(client, &session) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        },
+        Mode::Once(command) => run_once(&command, &client, &session),
+        Mode::Script(path) => run_script(&path, &client, &session),
+    };
+    std::process::exit(code);
+}
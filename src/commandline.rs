@@ -1,45 +1,221 @@
-use std::collections::LinkedList;
-use std::io::{self, Write};
+pub mod editor;
 
 use crate::errors::CommandLineError;
 
 #[derive(Debug, Clone)]
 pub struct CommandLine {
     command: String,
-    args: LinkedList<String>,
+    args: Vec<String>,
 }
 
 impl CommandLine {
-    fn new(command: String, args: LinkedList<String>) -> Self {
+    fn new(command: String, args: Vec<String>) -> Self {
         CommandLine { command, args }
     }
 
-    fn new_from_list(line: LinkedList<String>) -> Option<Self> {
+    fn new_from_list(line: Vec<String>) -> Option<Self> {
         let mut iter = line.into_iter();
         if let Some(command) = iter.next() {
-            let args: LinkedList<String> = iter.collect();
+            let args: Vec<String> = iter.collect();
             Some(CommandLine::new(command, args))
         } else {
             None
         }
     }
 
-    pub fn readline() -> Result<CommandLine, CommandLineError> {
-        let stdin = std::io::stdin();
-        let mut buf = String::new();
-        eprint!("> ");
-        io::stderr().flush()?;
-        stdin.read_line(&mut buf)?;
-        let list: std::collections::LinkedList<String> =
-            buf.split_whitespace().map(|s| s.to_string()).collect();
-        CommandLine::new_from_list(list).ok_or_else(|| CommandLineError::EmptyCommand)
+    pub fn parse(line: &str) -> Result<CommandLine, CommandLineError> {
+        let list = Self::tokenize(line)?;
+        CommandLine::new_from_list(list).ok_or(CommandLineError::EmptyCommand)
+    }
+
+    /// Builds a command from already split words, e.g. the rest of argv.
+    pub fn from_args(args: &[String]) -> Option<CommandLine> {
+        CommandLine::new_from_list(args.to_vec())
+    }
+
+    /// Splits a line into words the way a POSIX shell would: single quotes are
+    /// literal, double quotes only honour `\"` and `\\`, an unquoted backslash
+    /// escapes the next character and `#` at the start of a word begins a comment.
+    fn tokenize(line: &str) -> Result<Vec<String>, CommandLineError> {
+        #[derive(Debug, PartialEq)]
+        enum State {
+            Start,
+            Word,
+            Escape,
+            SingleQuoted,
+            DoubleQuoted,
+            DoubleQuotedEscape,
+        }
+
+        let mut list: Vec<String> = Vec::new();
+        let mut cur_word = String::new();
+        let mut cur_state = State::Start;
+        for c in line.chars() {
+            match cur_state {
+                State::Start => match c {
+                    '#' => break,
+                    c if c.is_whitespace() => {}
+                    '\\' => cur_state = State::Escape,
+                    '\'' => cur_state = State::SingleQuoted,
+                    '"' => cur_state = State::DoubleQuoted,
+                    c => {
+                        cur_word.push(c);
+                        cur_state = State::Word;
+                    }
+                },
+                State::Word => match c {
+                    c if c.is_whitespace() => {
+                        list.push(std::mem::take(&mut cur_word));
+                        cur_state = State::Start;
+                    }
+                    '\\' => cur_state = State::Escape,
+                    '\'' => cur_state = State::SingleQuoted,
+                    '"' => cur_state = State::DoubleQuoted,
+                    c => cur_word.push(c),
+                },
+                State::Escape => {
+                    cur_word.push(c);
+                    cur_state = State::Word;
+                }
+                State::SingleQuoted => match c {
+                    '\'' => cur_state = State::Word,
+                    c => cur_word.push(c),
+                },
+                State::DoubleQuoted => match c {
+                    '"' => cur_state = State::Word,
+                    '\\' => cur_state = State::DoubleQuotedEscape,
+                    c => cur_word.push(c),
+                },
+                State::DoubleQuotedEscape => {
+                    if c != '"' && c != '\\' {
+                        cur_word.push('\\');
+                    }
+                    cur_word.push(c);
+                    cur_state = State::DoubleQuoted;
+                }
+            }
+        }
+        match cur_state {
+            State::Start => {}
+            State::Word => list.push(cur_word),
+            State::Escape => return Err(CommandLineError::TrailingBackslash),
+            State::SingleQuoted => return Err(CommandLineError::UnterminatedQuote('\'')),
+            State::DoubleQuoted | State::DoubleQuotedEscape => {
+                return Err(CommandLineError::UnterminatedQuote('"'));
+            }
+        }
+        Ok(list)
     }
 
     pub fn command(&self) -> &String {
         &self.command
     }
 
-    pub fn args(&self) -> &LinkedList<String> {
+    pub fn args(&self) -> &[String] {
         &self.args
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod tokenize_tests {
+        use super::*;
+
+        #[test]
+        fn test_tokenize_whitespace() {
+            let result = CommandLine::tokenize("  signal\tHUP   cat  ").unwrap();
+            assert_eq!(result, vec!["signal", "HUP", "cat"]);
+        }
+
+        #[test]
+        fn test_tokenize_single_quotes() {
+            let result = CommandLine::tokenize("echo 'a b\\ \"c\"'").unwrap();
+            assert_eq!(result, vec!["echo", "a b\\ \"c\""]);
+        }
+
+        #[test]
+        fn test_tokenize_double_quotes() {
+            let result = CommandLine::tokenize("echo \"a 'b' \\\"c\\\" \\\\ \\n\"").unwrap();
+            assert_eq!(result, vec!["echo", "a 'b' \"c\" \\ \\n"]);
+        }
+
+        #[test]
+        fn test_tokenize_backslash() {
+            let result = CommandLine::tokenize("echo a\\ b \\'c").unwrap();
+            assert_eq!(result, vec!["echo", "a b", "'c"]);
+        }
+
+        #[test]
+        fn test_tokenize_adjacent_quotes() {
+            let result = CommandLine::tokenize("echo ab'c d'\"e\"f").unwrap();
+            assert_eq!(result, vec!["echo", "abc def"]);
+        }
+
+        #[test]
+        fn test_tokenize_empty_quotes() {
+            let result = CommandLine::tokenize("echo '' \"\"").unwrap();
+            assert_eq!(result, vec!["echo", "", ""]);
+        }
+
+        #[test]
+        fn test_tokenize_comment() {
+            let result = CommandLine::tokenize("pid all # every process").unwrap();
+            assert_eq!(result, vec!["pid", "all"]);
+        }
+
+        #[test]
+        fn test_tokenize_hash_inside_word() {
+            let result = CommandLine::tokenize("echo a#b '#c'").unwrap();
+            assert_eq!(result, vec!["echo", "a#b", "#c"]);
+        }
+
+        #[test]
+        fn test_tokenize_only_comment() {
+            let result = CommandLine::tokenize("   # nothing").unwrap();
+            assert!(result.is_empty());
+        }
+
+        #[test]
+        fn test_tokenize_unterminated_single_quote() {
+            let result = CommandLine::tokenize("echo 'abc");
+            assert!(matches!(
+                result,
+                Err(CommandLineError::UnterminatedQuote('\''))
+            ));
+        }
+
+        #[test]
+        fn test_tokenize_unterminated_double_quote() {
+            let result = CommandLine::tokenize("echo \"abc\\\"");
+            assert!(matches!(
+                result,
+                Err(CommandLineError::UnterminatedQuote('"'))
+            ));
+        }
+
+        #[test]
+        fn test_tokenize_trailing_backslash() {
+            let result = CommandLine::tokenize("echo abc\\");
+            assert!(matches!(result, Err(CommandLineError::TrailingBackslash)));
+        }
+    }
+
+    mod parse_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_command_and_args() {
+            let line = CommandLine::parse("signal USR1 'web:*'").unwrap();
+            assert_eq!(line.command(), "signal");
+            assert_eq!(line.args(), ["USR1", "web:*"]);
+        }
+
+        #[test]
+        fn test_parse_empty() {
+            let result = CommandLine::parse("   ");
+            assert!(matches!(result, Err(CommandLineError::EmptyCommand)));
+        }
+    }
+}
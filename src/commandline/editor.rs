@@ -0,0 +1,174 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+
+use crate::commandline::CommandLine;
+use crate::control::client::Client;
+use crate::errors::CommandLineError;
+use crate::exec::registry::{ArgKind, Registry};
+use crate::exec::signal;
+
+const HISTORY_FILE: &str = ".taskmaster_history";
+const PROMPT: &str = "> ";
+
+/// Completes command names in the first word and then whatever the
+/// command's argument schema expects at the cursor.
+struct ShellHelper {
+    registry: Arc<Registry>,
+    client: Arc<Client>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        // without taskmasterd there are just no process names to offer
+        let names = self.client.completion_names().unwrap_or_default();
+        Ok(complete(line, pos, &self.registry, &names))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+fn complete(line: &str, pos: usize, registry: &Registry, names: &[String]) -> (usize, Vec<Pair>) {
+    let start = line[..pos].rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = &line[start..pos];
+    let mut previous = line[..start].split_whitespace();
+    let kind = match previous.next() {
+        None => Some(ArgKind::Command),
+        Some(command) => registry
+            .find(command)
+            .and_then(|c| c.arg_kind(previous.count())),
+    };
+    let candidates: Vec<&str> = match kind {
+        Some(ArgKind::Command) => registry.names(),
+        Some(ArgKind::Process) => names.iter().map(String::as_str).collect(),
+        Some(ArgKind::Signal) => signal::names().collect(),
+        Some(ArgKind::Choice(choices)) => choices.to_vec(),
+        None => Vec::new(),
+    };
+    let pairs = candidates
+        .into_iter()
+        .filter(|c| c.starts_with(word))
+        .map(|c| Pair {
+            display: c.to_string(),
+            replacement: c.to_string(),
+        })
+        .collect();
+    (start, pairs)
+}
+
+pub struct LineEditor {
+    editor: Editor<ShellHelper, FileHistory>,
+    history: Option<PathBuf>,
+}
+
+impl LineEditor {
+    pub fn new(registry: Arc<Registry>, client: Arc<Client>) -> Result<Self, CommandLineError> {
+        let config = Config::builder()
+            .completion_type(CompletionType::List)
+            .auto_add_history(false)
+            .build();
+        let mut editor = Editor::with_config(config)?;
+        editor.set_helper(Some(ShellHelper { registry, client }));
+        let history = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        if let Some(path) = &history {
+            // a missing history file just means this is the first session
+            let _ = editor.load_history(path);
+        }
+        Ok(LineEditor { editor, history })
+    }
+
+    pub fn readline(&mut self) -> Result<CommandLine, CommandLineError> {
+        let line = match self.editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => return Err(CommandLineError::EmptyCommand),
+            Err(ReadlineError::Eof) => return Err(CommandLineError::Eof),
+            Err(e) => return Err(e.into()),
+        };
+        if !line.trim().is_empty() {
+            self.editor.add_history_entry(line.as_str())?;
+            if let Some(path) = &self.history {
+                self.editor.save_history(path)?;
+            }
+        }
+        CommandLine::parse(&line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::commands;
+
+    fn names() -> Vec<String> {
+        ["all", "cat:*", "cat:cat0", "cat0"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn replacements(pairs: Vec<Pair>) -> Vec<String> {
+        pairs.into_iter().map(|p| p.replacement).collect()
+    }
+
+    #[test]
+    fn test_complete_command() {
+        let (start, pairs) = complete("si", 2, &commands::registry(), &names());
+        assert_eq!(start, 0);
+        assert_eq!(replacements(pairs), vec!["signal"]);
+    }
+
+    #[test]
+    fn test_complete_process_name() {
+        let (start, pairs) = complete("pid ca", 6, &commands::registry(), &names());
+        assert_eq!(start, 4);
+        assert_eq!(replacements(pairs), vec!["cat:*", "cat:cat0", "cat0"]);
+    }
+
+    #[test]
+    fn test_complete_signal_name() {
+        let (start, pairs) = complete("signal US", 9, &commands::registry(), &names());
+        assert_eq!(start, 7);
+        assert_eq!(replacements(pairs), vec!["USR1", "USR2"]);
+    }
+
+    #[test]
+    fn test_complete_repeated_argument() {
+        let (start, pairs) = complete("signal HUP cat0 a", 17, &commands::registry(), &names());
+        assert_eq!(start, 16);
+        assert_eq!(replacements(pairs), vec!["all"]);
+    }
+
+    #[test]
+    fn test_complete_help_topic() {
+        let (_, pairs) = complete("help st", 7, &commands::registry(), &names());
+        assert_eq!(replacements(pairs), vec!["start", "status", "stop"]);
+    }
+
+    #[test]
+    fn test_complete_beyond_schema() {
+        let (_, pairs) = complete("fg cat0 ", 8, &commands::registry(), &names());
+        assert!(pairs.is_empty());
+    }
+}
@@ -1,8 +1,12 @@
 pub mod adapter;
 pub mod config;
+pub mod eventlistener;
+pub mod inet_http_server;
 pub mod logger;
+pub mod notify;
 pub mod parser;
 pub mod program;
 pub mod runtimecontext;
 pub mod section;
 pub mod taskmasterd;
+pub mod unix_http_server;
@@ -1,8 +1,12 @@
 pub struct Adapter;
 
+use crate::config::eventlistener::{eventlistener, EventListener, EventListenerSection};
+use crate::config::inet_http_server::{inet_http_server, InetHttpServerSection};
+use crate::config::notify::{notify, Notify, NotifySection};
 use crate::config::program::program;
+use crate::config::unix_http_server::{unix_http_server, UnixHttpServerSection};
 use crate::config::{
-    parser::ProgramParser,
+    parser::{ListenerParser, NotifyParser, ProgramParser, ServerParser},
     taskmasterd::{taskmasterd, TaskmasterdSection},
 };
 use crate::errors::ProgramBuilderError;
@@ -32,10 +36,26 @@ impl Adapter {
                     rc.logger.debug("Parsing taskmasterd section.");
                     Self::parse_taskmasterd(rc, prop)?;
                 }
+                Some(unix_http_server::UNIX_HTTP_SERVER) => {
+                    rc.logger.debug("Parsing unix_http_server section.");
+                    Self::parse_unix_http_server(rc, prop)?;
+                }
+                Some(inet_http_server::INET_HTTP_SERVER) => {
+                    rc.logger.debug("Parsing inet_http_server section.");
+                    Self::parse_inet_http_server(rc, prop)?;
+                }
+                Some(notify::NOTIFY) => {
+                    rc.logger.debug("Parsing notify section.");
+                    Self::parse_notify(rc, prop)?;
+                }
                 Some(s) if s.starts_with(program::PROGRAM) => {
                     rc.logger.debug(&format!("Parsing program section: {}", s));
                     Self::parse_program(rc, s, prop)?;
                 }
+                Some(s) if s.starts_with(eventlistener::EVENTLISTENER) => {
+                    rc.logger.debug(&format!("Parsing eventlistener section: {}", s));
+                    Self::parse_program(rc, s, prop)?;
+                }
                 Some(s) => {
                     rc.logger.error(&format!("Parsing unknown section: {}", s));
                     return Err(ConfigParseError::UnexpectedValue(s.to_string()));
@@ -46,6 +66,29 @@ impl Adapter {
                 }
             }
         }
+        // sections come in any order, so childlogdir is only known now
+        if let Some(childlogdir) = rc.config.taskmasterd.childlogdir.clone() {
+            for program in rc.config.programs.values_mut() {
+                program.place_logfiles(&childlogdir);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads only the `[unix_http_server]` section, which is all taskmasterctl
+    /// needs to find the daemon.
+    pub fn parse_client_config(
+        rc: &mut RuntimeContext,
+        file_path: Option<&String>,
+    ) -> Result<(), ConfigParseError> {
+        let file_path = match file_path {
+            Some(path) => path.to_string(),
+            None => Config::find_config()?,
+        };
+        let ini = Ini::load_from_file(file_path)?;
+        if let Some(prop) = ini.section(Some(unix_http_server::UNIX_HTTP_SERVER)) {
+            Self::parse_unix_http_server(rc, prop)?;
+        }
         Ok(())
     }
 
@@ -70,12 +113,118 @@ impl Adapter {
                     config.taskmasterd.loglevel = value;
                     logger.change_level(value);
                 }
+                TaskmasterdSection::Nodaemon => {
+                    config.taskmasterd.nodaemon = ProgramParser::parse_autostart(value)?;
+                }
+                TaskmasterdSection::Pidfile => {
+                    config.taskmasterd.pidfile = value.to_string();
+                }
+                TaskmasterdSection::Directory => {
+                    config.taskmasterd.directory = Some(value.to_string());
+                }
+                TaskmasterdSection::Umask => {
+                    config.taskmasterd.umask = ServerParser::parse_chmod(value)?;
+                }
+                TaskmasterdSection::Childlogdir => {
+                    config.taskmasterd.childlogdir = Some(value.to_string());
+                }
             }
             logger.enable();
         }
         Ok(())
     }
 
+    fn parse_unix_http_server(
+        rc: &mut RuntimeContext,
+        prop: &Properties,
+    ) -> Result<(), ConfigParseError> {
+        let server = &mut rc.config.unix_http_server;
+        for (key, value) in prop.iter() {
+            let section_value = UnixHttpServerSection::from_str(key)
+                .ok_or_else(|| ConfigParseError::UnexpectedValue(key.to_string()))?;
+            match section_value {
+                UnixHttpServerSection::File => {
+                    server.file = value.to_string();
+                }
+                UnixHttpServerSection::Chmod => {
+                    server.chmod = ServerParser::parse_chmod(value)?;
+                }
+                UnixHttpServerSection::Chown => {
+                    server.chown = Some(ServerParser::parse_chown(value)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_inet_http_server(
+        rc: &mut RuntimeContext,
+        prop: &Properties,
+    ) -> Result<(), ConfigParseError> {
+        let server = &mut rc.config.inet_http_server;
+        for (key, value) in prop.iter() {
+            let section_value = InetHttpServerSection::from_str(key)
+                .ok_or_else(|| ConfigParseError::UnexpectedValue(key.to_string()))?;
+            match section_value {
+                InetHttpServerSection::Port => {
+                    server.port = Some(ServerParser::parse_port(value)?);
+                }
+                InetHttpServerSection::Username => {
+                    server.username = Some(value.to_string());
+                }
+                InetHttpServerSection::Password => {
+                    server.password = Some(value.to_string());
+                }
+            }
+        }
+        if server.port.is_none() {
+            return Err(ConfigParseError::MissingPort);
+        }
+        if server.username.is_some() != server.password.is_some() {
+            return Err(ConfigParseError::IncompleteCredentials);
+        }
+        Ok(())
+    }
+
+    fn parse_notify(rc: &mut RuntimeContext, prop: &Properties) -> Result<(), ConfigParseError> {
+        let mut notify = Notify::default();
+        for (key, value) in prop.iter() {
+            let section_value = NotifySection::from_str(key)
+                .ok_or_else(|| ConfigParseError::UnexpectedValue(key.to_string()))?;
+            match section_value {
+                NotifySection::Mailto => {
+                    notify.mailto = NotifyParser::parse_mailto(value)?;
+                }
+                NotifySection::From => {
+                    notify.from = value.to_string();
+                }
+                NotifySection::Sendmail => {
+                    notify.sendmail = NotifyParser::parse_sendmail(value)?;
+                }
+                NotifySection::SmtpHost => {
+                    notify.smtp_host = Some(value.to_string());
+                }
+                NotifySection::SmtpPort => {
+                    notify.smtp_port = NotifyParser::parse_smtp_port(value)?;
+                }
+                NotifySection::MaxRestarts => {
+                    notify.max_restarts = Some(NotifyParser::parse_max_restarts(value)?);
+                }
+                NotifySection::RestartWindow => {
+                    notify.restart_window = NotifyParser::parse_restart_window(value)?;
+                }
+                NotifySection::StderrLines => {
+                    notify.stderr_lines = NotifyParser::parse_stderr_lines(value)?;
+                }
+            }
+        }
+        if notify.mailto.is_empty() {
+            return Err(ConfigParseError::MissingRecipients);
+        }
+        rc.config.notify = Some(notify);
+        Ok(())
+    }
+
     fn parse_program(
         rc: &mut RuntimeContext,
         sec: &str,
@@ -92,7 +241,22 @@ impl Adapter {
         }
         let mut builder = Program::builder();
         builder.programname(program_name.clone());
+        // event listeners take every program key, and their events
+        let is_listener = parts[0] == eventlistener::EVENTLISTENER;
+        let mut events = None;
+        let mut buffer_size = eventlistener::DEFAULT_BUFFER_SIZE;
         for (key, value) in prop.iter() {
+            if is_listener && let Some(section_value) = EventListenerSection::from_str(key) {
+                match section_value {
+                    EventListenerSection::Events => {
+                        events = Some(ListenerParser::parse_events(value)?);
+                    }
+                    EventListenerSection::BufferSize => {
+                        buffer_size = ListenerParser::parse_buffer_size(value)?;
+                    }
+                }
+                continue;
+            }
             let section_value = ProgramSection::from_str(key)
                 .ok_or_else(|| ConfigParseError::UnexpectedValue(key.to_string()))?;
             match section_value {
@@ -139,8 +303,39 @@ impl Adapter {
                 ProgramSection::Umask => {
                     builder.umask(ProgramParser::parse_umask(value)?);
                 }
+                ProgramSection::Priority => {
+                    builder.priority(ProgramParser::parse_priority(value)?);
+                }
+                ProgramSection::StdoutEventsEnabled => {
+                    builder.stdout_events_enabled(ProgramParser::parse_events_enabled(value)?);
+                }
+                ProgramSection::StderrEventsEnabled => {
+                    builder.stderr_events_enabled(ProgramParser::parse_events_enabled(value)?);
+                }
+                ProgramSection::OnStart => {
+                    builder.on_start(ProgramParser::parse_hook(value)?);
+                }
+                ProgramSection::OnExit => {
+                    builder.on_exit(ProgramParser::parse_hook(value)?);
+                }
+                ProgramSection::OnBackoff => {
+                    builder.on_backoff(ProgramParser::parse_hook(value)?);
+                }
+                ProgramSection::OnFatal => {
+                    builder.on_fatal(ProgramParser::parse_hook(value)?);
+                }
+                ProgramSection::HookTimeout => {
+                    builder.hook_timeout(ProgramParser::parse_hook_timeout(value)?);
+                }
             }
         }
+        if is_listener {
+            let events = events.ok_or_else(|| ConfigParseError::MissingEvents(program_name.clone()))?;
+            builder.listener(EventListener {
+                events,
+                buffer_size,
+            });
+        }
         let program = builder.build().map_err(|e| match e {
             ProgramBuilderError::MissingCommand => {
                 ConfigParseError::MissingCommand(program_name.clone())
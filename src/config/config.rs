@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 
+use crate::config::inet_http_server::InetHttpServer;
+use crate::config::notify::Notify;
+use crate::config::unix_http_server::UnixHttpServer;
 use crate::{config::config, config::taskmasterd::Taskmasterd};
 use crate::{config::program::Program, errors::ConfigParseError};
 
@@ -7,6 +10,9 @@ use crate::{config::program::Program, errors::ConfigParseError};
 pub struct Config {
     pub(in crate::config) programs: HashMap<String, Program>,
     pub(in crate::config) taskmasterd: Taskmasterd,
+    pub(in crate::config) unix_http_server: UnixHttpServer,
+    pub(in crate::config) inet_http_server: InetHttpServer,
+    pub(in crate::config) notify: Option<Notify>,
 }
 
 impl Config {
@@ -14,6 +20,9 @@ impl Config {
         Config {
             programs: HashMap::new(),
             taskmasterd: Taskmasterd::default(),
+            unix_http_server: UnixHttpServer::default(),
+            inet_http_server: InetHttpServer::default(),
+            notify: None,
         }
     }
 
@@ -21,6 +30,26 @@ impl Config {
         self.programs.get(name)
     }
 
+    pub fn programs(&self) -> &HashMap<String, Program> {
+        &self.programs
+    }
+
+    pub fn taskmasterd(&self) -> &Taskmasterd {
+        &self.taskmasterd
+    }
+
+    pub fn unix_http_server(&self) -> &UnixHttpServer {
+        &self.unix_http_server
+    }
+
+    pub fn inet_http_server(&self) -> &InetHttpServer {
+        &self.inet_http_server
+    }
+
+    pub fn notify(&self) -> Option<&Notify> {
+        self.notify.as_ref()
+    }
+
     pub fn find_program(&self, program: &String) -> Option<&Program> {
         self.programs.get(program)
     }
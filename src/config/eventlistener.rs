@@ -0,0 +1,72 @@
+/// What makes a program an event listener: the events its processes receive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventListener {
+    pub(in crate::config) events: Vec<String>, // event types, parents included
+    pub(in crate::config) buffer_size: usize,  // events kept while every listener is busy
+}
+
+impl EventListener {
+    pub fn events(&self) -> &Vec<String> {
+        &self.events
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventListenerSection {
+    Events,
+    BufferSize,
+}
+
+impl EventListenerSection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventListenerSection::Events => eventlistener::EVENTS,
+            EventListenerSection::BufferSize => eventlistener::BUFFER_SIZE,
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            eventlistener::EVENTS => Some(EventListenerSection::Events),
+            eventlistener::BUFFER_SIZE => Some(EventListenerSection::BufferSize),
+            _ => None,
+        }
+    }
+}
+
+pub mod eventlistener {
+    pub const EVENTLISTENER: &str = "eventlistener";
+    pub const EVENTS: &str = "events";
+    pub const BUFFER_SIZE: &str = "buffer_size";
+
+    pub const DEFAULT_BUFFER_SIZE: usize = 10;
+
+    /// Every event type a listener can subscribe to; a type also matches
+    /// its subtypes, like `PROCESS_STATE` does `PROCESS_STATE_EXITED`.
+    pub const EVENT_TYPES: [&str; 20] = [
+        "EVENT",
+        "PROCESS_STATE",
+        "PROCESS_STATE_STOPPED",
+        "PROCESS_STATE_STARTING",
+        "PROCESS_STATE_RUNNING",
+        "PROCESS_STATE_BACKOFF",
+        "PROCESS_STATE_STOPPING",
+        "PROCESS_STATE_EXITED",
+        "PROCESS_STATE_FATAL",
+        "PROCESS_STATE_UNKNOWN",
+        "PROCESS_LOG",
+        "PROCESS_LOG_STDOUT",
+        "PROCESS_LOG_STDERR",
+        "TICK",
+        "TICK_5",
+        "TICK_60",
+        "TICK_3600",
+        "SUPERVISOR_STATE_CHANGE",
+        "SUPERVISOR_STATE_CHANGE_RUNNING",
+        "SUPERVISOR_STATE_CHANGE_STOPPING",
+    ];
+}
@@ -0,0 +1,53 @@
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InetHttpServer {
+    pub(in crate::config) port: Option<(String, u16)>, // host and port to listen on
+    pub(in crate::config) username: Option<String>,
+    pub(in crate::config) password: Option<String>, // plaintext, or {SHA} and the hex SHA-1
+}
+
+impl InetHttpServer {
+    pub fn port(&self) -> Option<&(String, u16)> {
+        self.port.as_ref()
+    }
+
+    pub fn username(&self) -> Option<&String> {
+        self.username.as_ref()
+    }
+
+    pub fn password(&self) -> Option<&String> {
+        self.password.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InetHttpServerSection {
+    Port,
+    Username,
+    Password,
+}
+
+impl InetHttpServerSection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InetHttpServerSection::Port => inet_http_server::PORT,
+            InetHttpServerSection::Username => inet_http_server::USERNAME,
+            InetHttpServerSection::Password => inet_http_server::PASSWORD,
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            inet_http_server::PORT => Some(InetHttpServerSection::Port),
+            inet_http_server::USERNAME => Some(InetHttpServerSection::Username),
+            inet_http_server::PASSWORD => Some(InetHttpServerSection::Password),
+            _ => None,
+        }
+    }
+}
+
+pub mod inet_http_server {
+    pub const INET_HTTP_SERVER: &str = "inet_http_server";
+    pub const PORT: &str = "port";
+    pub const USERNAME: &str = "username";
+    pub const PASSWORD: &str = "password";
+}
@@ -0,0 +1,123 @@
+/// Who is mailed when a process dies, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notify {
+    pub(in crate::config) mailto: Vec<String>, // recipients
+    pub(in crate::config) from: String,
+    pub(in crate::config) sendmail: Vec<String>, // command the mail is piped to
+    pub(in crate::config) smtp_host: Option<String>, // SMTP is used instead of sendmail when set
+    pub(in crate::config) smtp_port: u16,
+    pub(in crate::config) max_restarts: Option<u32>, // restarts within restart_window that mail a crash loop
+    pub(in crate::config) restart_window: u64,       // seconds
+    pub(in crate::config) stderr_lines: usize,       // lines of stderr quoted in the mail
+}
+
+impl Notify {
+    pub fn mailto(&self) -> &Vec<String> {
+        &self.mailto
+    }
+
+    pub fn from(&self) -> &String {
+        &self.from
+    }
+
+    pub fn sendmail(&self) -> &Vec<String> {
+        &self.sendmail
+    }
+
+    pub fn smtp_host(&self) -> Option<&String> {
+        self.smtp_host.as_ref()
+    }
+
+    pub fn smtp_port(&self) -> u16 {
+        self.smtp_port
+    }
+
+    pub fn max_restarts(&self) -> Option<u32> {
+        self.max_restarts
+    }
+
+    pub fn restart_window(&self) -> u64 {
+        self.restart_window
+    }
+
+    pub fn stderr_lines(&self) -> usize {
+        self.stderr_lines
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Notify {
+            mailto: Vec::new(),
+            from: notify::DEFAULT_FROM.to_string(),
+            sendmail: notify::DEFAULT_SENDMAIL
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            smtp_host: None,
+            smtp_port: notify::DEFAULT_SMTP_PORT,
+            max_restarts: None,
+            restart_window: notify::DEFAULT_RESTART_WINDOW,
+            stderr_lines: notify::DEFAULT_STDERR_LINES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifySection {
+    Mailto,
+    From,
+    Sendmail,
+    SmtpHost,
+    SmtpPort,
+    MaxRestarts,
+    RestartWindow,
+    StderrLines,
+}
+
+impl NotifySection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotifySection::Mailto => notify::MAILTO,
+            NotifySection::From => notify::FROM,
+            NotifySection::Sendmail => notify::SENDMAIL,
+            NotifySection::SmtpHost => notify::SMTP_HOST,
+            NotifySection::SmtpPort => notify::SMTP_PORT,
+            NotifySection::MaxRestarts => notify::MAX_RESTARTS,
+            NotifySection::RestartWindow => notify::RESTART_WINDOW,
+            NotifySection::StderrLines => notify::STDERR_LINES,
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            notify::MAILTO => Some(NotifySection::Mailto),
+            notify::FROM => Some(NotifySection::From),
+            notify::SENDMAIL => Some(NotifySection::Sendmail),
+            notify::SMTP_HOST => Some(NotifySection::SmtpHost),
+            notify::SMTP_PORT => Some(NotifySection::SmtpPort),
+            notify::MAX_RESTARTS => Some(NotifySection::MaxRestarts),
+            notify::RESTART_WINDOW => Some(NotifySection::RestartWindow),
+            notify::STDERR_LINES => Some(NotifySection::StderrLines),
+            _ => None,
+        }
+    }
+}
+
+pub mod notify {
+    pub const NOTIFY: &str = "notify";
+    pub const MAILTO: &str = "mailto";
+    pub const FROM: &str = "from";
+    pub const SENDMAIL: &str = "sendmail";
+    pub const SMTP_HOST: &str = "smtp_host";
+    pub const SMTP_PORT: &str = "smtp_port";
+    pub const MAX_RESTARTS: &str = "max_restarts";
+    pub const RESTART_WINDOW: &str = "restart_window";
+    pub const STDERR_LINES: &str = "stderr_lines";
+
+    pub const DEFAULT_FROM: &str = "taskmasterd@localhost";
+    pub const DEFAULT_SENDMAIL: [&str; 3] = ["/usr/sbin/sendmail", "-t", "-i"];
+    pub const DEFAULT_SMTP_PORT: u16 = 25;
+    pub const DEFAULT_RESTART_WINDOW: u64 = 60;
+    pub const DEFAULT_STDERR_LINES: usize = 20;
+}
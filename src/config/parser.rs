@@ -2,6 +2,7 @@ use std::collections::LinkedList;
 
 use crate::errors::ConfigParseError;
 
+use super::eventlistener::eventlistener;
 use super::program::program::AutoRestart;
 
 pub struct ProgramParser;
@@ -190,6 +191,155 @@ impl ProgramParser {
             .parse::<u16>()
             .map_err(|_| ConfigParseError::UnexpectedValue(umask.to_string()))
     }
+
+    pub fn parse_priority(priority: &str) -> Result<i32, ConfigParseError> {
+        priority
+            .parse::<i32>()
+            .map_err(|_| ConfigParseError::UnexpectedValue(priority.to_string()))
+    }
+
+    /// A hook is run by the shell, so it is kept as written.
+    pub fn parse_hook(hook: &str) -> Result<String, ConfigParseError> {
+        match hook.trim() {
+            "" => Err(ConfigParseError::UnexpectedValue(hook.to_string())),
+            hook => Ok(hook.to_string()),
+        }
+    }
+
+    pub fn parse_hook_timeout(hook_timeout: &str) -> Result<u32, ConfigParseError> {
+        hook_timeout
+            .parse::<u32>()
+            .ok()
+            .filter(|secs| *secs > 0)
+            .ok_or_else(|| ConfigParseError::UnexpectedValue(hook_timeout.to_string()))
+    }
+
+    pub fn parse_events_enabled(enabled: &str) -> Result<bool, ConfigParseError> {
+        Self::parse_autostart(enabled)
+    }
+}
+
+pub struct NotifyParser;
+
+impl NotifyParser {
+    /// Parses a comma separated list of mail addresses.
+    pub fn parse_mailto(mailto: &str) -> Result<Vec<String>, ConfigParseError> {
+        let list = mailto
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| match s.contains('@') && !s.contains(char::is_whitespace) {
+                true => Ok(s.to_string()),
+                false => Err(ConfigParseError::UnexpectedValue(s.to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if list.is_empty() {
+            Err(ConfigParseError::UnexpectedValue(mailto.to_string()))?;
+        }
+        Ok(list)
+    }
+
+    /// The sendmail command and its arguments.
+    pub fn parse_sendmail(sendmail: &str) -> Result<Vec<String>, ConfigParseError> {
+        Ok(ProgramParser::parse_command(sendmail)?.into_iter().collect())
+    }
+
+    pub fn parse_smtp_port(port: &str) -> Result<u16, ConfigParseError> {
+        port.parse::<u16>()
+            .ok()
+            .filter(|port| *port > 0)
+            .ok_or_else(|| ConfigParseError::UnexpectedValue(port.to_string()))
+    }
+
+    pub fn parse_max_restarts(max_restarts: &str) -> Result<u32, ConfigParseError> {
+        max_restarts
+            .parse::<u32>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| ConfigParseError::UnexpectedValue(max_restarts.to_string()))
+    }
+
+    pub fn parse_restart_window(restart_window: &str) -> Result<u64, ConfigParseError> {
+        restart_window
+            .parse::<u64>()
+            .ok()
+            .filter(|secs| *secs > 0)
+            .ok_or_else(|| ConfigParseError::UnexpectedValue(restart_window.to_string()))
+    }
+
+    pub fn parse_stderr_lines(stderr_lines: &str) -> Result<usize, ConfigParseError> {
+        stderr_lines
+            .parse::<usize>()
+            .map_err(|_| ConfigParseError::UnexpectedValue(stderr_lines.to_string()))
+    }
+}
+
+pub struct ListenerParser;
+
+impl ListenerParser {
+    /// Parses a comma separated list of event types.
+    pub fn parse_events(events: &str) -> Result<Vec<String>, ConfigParseError> {
+        let list = events
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| match eventlistener::EVENT_TYPES.contains(&s) {
+                true => Ok(s.to_string()),
+                false => Err(ConfigParseError::UnexpectedValue(s.to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if list.is_empty() {
+            Err(ConfigParseError::UnexpectedValue(events.to_string()))?;
+        }
+        Ok(list)
+    }
+
+    pub fn parse_buffer_size(buffer_size: &str) -> Result<usize, ConfigParseError> {
+        buffer_size
+            .parse::<usize>()
+            .ok()
+            .filter(|size| *size > 0)
+            .ok_or_else(|| ConfigParseError::UnexpectedValue(buffer_size.to_string()))
+    }
+}
+
+pub struct ServerParser;
+
+impl ServerParser {
+    /// Parses permission bits written in octal, like `0770`.
+    pub fn parse_chmod(chmod: &str) -> Result<u32, ConfigParseError> {
+        u32::from_str_radix(chmod, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o7777)
+            .ok_or_else(|| ConfigParseError::UnexpectedValue(chmod.to_string()))
+    }
+
+    /// Parses `user` or `user:group`.
+    pub fn parse_chown(chown: &str) -> Result<(String, Option<String>), ConfigParseError> {
+        let (user, group) = match chown.split_once(':') {
+            Some((user, group)) => (user, Some(group)),
+            None => (chown, None),
+        };
+        if user.is_empty() || group.is_some_and(str::is_empty) {
+            return Err(ConfigParseError::UnexpectedValue(chown.to_string()));
+        }
+        Ok((user.to_string(), group.map(String::from)))
+    }
+
+    /// Parses `host:port`, where `*` or no host at all means every interface.
+    pub fn parse_port(port: &str) -> Result<(String, u16), ConfigParseError> {
+        let (host, number) = port.rsplit_once(':').unwrap_or(("", port));
+        let number = number
+            .parse::<u16>()
+            .ok()
+            .filter(|number| *number != 0)
+            .ok_or_else(|| ConfigParseError::UnexpectedValue(port.to_string()))?;
+        let host = match host.trim_start_matches('[').trim_end_matches(']') {
+            "" | "*" => "0.0.0.0",
+            host => host,
+        };
+        Ok((host.to_string(), number))
+    }
 }
 
 #[cfg(test)]
@@ -637,4 +787,167 @@ mod tests {
             assert!(result.is_err());
         }
     }
+
+    mod parse_priority_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_priority_valid() {
+            let priority = "100";
+            let result = ProgramParser::parse_priority(priority).unwrap();
+            assert_eq!(result, 100);
+        }
+
+        #[test]
+        fn test_parse_priority_invalid() {
+            let priority = "abc";
+            let result = ProgramParser::parse_priority(priority);
+            assert!(result.is_err());
+        }
+    }
+
+    mod parse_hook_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_hook_valid() {
+            let hook = "echo $TASKMASTER_PROCESS_NAME >> /tmp/fatal.log";
+            let result = ProgramParser::parse_hook(hook).unwrap();
+            assert_eq!(result, hook);
+        }
+
+        #[test]
+        fn test_parse_hook_empty() {
+            assert!(ProgramParser::parse_hook("  ").is_err());
+        }
+
+        #[test]
+        fn test_parse_hook_timeout() {
+            assert_eq!(ProgramParser::parse_hook_timeout("30").unwrap(), 30);
+            assert!(ProgramParser::parse_hook_timeout("0").is_err());
+            assert!(ProgramParser::parse_hook_timeout("abc").is_err());
+        }
+    }
+
+    mod parse_chmod_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_chmod_valid() {
+            let result = ServerParser::parse_chmod("0770").unwrap();
+            assert_eq!(result, 0o770);
+        }
+
+        #[test]
+        fn test_parse_chmod_not_octal() {
+            let result = ServerParser::parse_chmod("0789");
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn test_parse_chmod_out_of_range() {
+            let result = ServerParser::parse_chmod("17777");
+            assert!(result.is_err());
+        }
+    }
+
+    mod parse_chown_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_chown_user() {
+            let result = ServerParser::parse_chown("nobody").unwrap();
+            assert_eq!(result, ("nobody".to_string(), None));
+        }
+
+        #[test]
+        fn test_parse_chown_user_and_group() {
+            let result = ServerParser::parse_chown("nobody:nogroup").unwrap();
+            assert_eq!(result, ("nobody".to_string(), Some("nogroup".to_string())));
+        }
+
+        #[test]
+        fn test_parse_chown_empty_group() {
+            let result = ServerParser::parse_chown("nobody:");
+            assert!(result.is_err());
+        }
+    }
+
+    mod parse_events_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_events_valid() {
+            let result = ListenerParser::parse_events("PROCESS_STATE_EXITED, TICK_60").unwrap();
+            assert_eq!(result, vec!["PROCESS_STATE_EXITED", "TICK_60"]);
+        }
+
+        #[test]
+        fn test_parse_events_unknown() {
+            assert!(ListenerParser::parse_events("PROCESS_STATE,TOCK_5").is_err());
+            assert!(ListenerParser::parse_events(" , ").is_err());
+        }
+
+        #[test]
+        fn test_parse_buffer_size() {
+            assert_eq!(ListenerParser::parse_buffer_size("25").unwrap(), 25);
+            assert!(ListenerParser::parse_buffer_size("0").is_err());
+            assert!(ListenerParser::parse_buffer_size("-1").is_err());
+        }
+    }
+
+    mod parse_notify_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_mailto() {
+            let result = NotifyParser::parse_mailto("ops@example.com, oncall@example.com").unwrap();
+            assert_eq!(result, vec!["ops@example.com", "oncall@example.com"]);
+            assert!(NotifyParser::parse_mailto("ops").is_err());
+            assert!(NotifyParser::parse_mailto(",").is_err());
+        }
+
+        #[test]
+        fn test_parse_sendmail() {
+            let result = NotifyParser::parse_sendmail("/usr/bin/msmtp -t").unwrap();
+            assert_eq!(result, vec!["/usr/bin/msmtp", "-t"]);
+        }
+
+        #[test]
+        fn test_parse_smtp_port() {
+            assert_eq!(NotifyParser::parse_smtp_port("2525").unwrap(), 2525);
+            assert!(NotifyParser::parse_smtp_port("0").is_err());
+            assert!(NotifyParser::parse_smtp_port("70000").is_err());
+        }
+
+        #[test]
+        fn test_parse_max_restarts() {
+            assert_eq!(NotifyParser::parse_max_restarts("5").unwrap(), 5);
+            assert!(NotifyParser::parse_max_restarts("0").is_err());
+        }
+    }
+
+    mod parse_port_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_port_with_host() {
+            let result = ServerParser::parse_port("127.0.0.1:9001").unwrap();
+            assert_eq!(result, ("127.0.0.1".to_string(), 9001));
+        }
+
+        #[test]
+        fn test_parse_port_any_host() {
+            let expected = ("0.0.0.0".to_string(), 9001);
+            assert_eq!(ServerParser::parse_port("*:9001").unwrap(), expected);
+            assert_eq!(ServerParser::parse_port("9001").unwrap(), expected);
+        }
+
+        #[test]
+        fn test_parse_port_invalid() {
+            assert!(ServerParser::parse_port("localhost:http").is_err());
+            assert!(ServerParser::parse_port("localhost:0").is_err());
+            assert!(ServerParser::parse_port("localhost:70000").is_err());
+        }
+    }
 }
@@ -1,9 +1,11 @@
 use signal_hook::consts::signal::SIGTERM;
 use std::collections::{HashSet, LinkedList};
+use std::path::Path;
 
+use crate::config::eventlistener::EventListener;
 use crate::errors::ProgramBuilderError;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Program {
     pub(in crate::config) programname: String, // unique identifier for the program
     pub(in crate::config) command: LinkedList<String>,
@@ -20,6 +22,15 @@ pub struct Program {
     pub(in crate::config) enviroment: Option<LinkedList<String>>, // environment variables to set for the program
     pub(in crate::config) directory: Option<String>, // working directory for the program
     pub(in crate::config) umask: Option<u16>,        // working directory for the program
+    pub(in crate::config) priority: i32,             // relative order for starting and stopping
+    pub(in crate::config) stdout_events_enabled: bool, // whether stdout is sent as PROCESS_LOG events
+    pub(in crate::config) stderr_events_enabled: bool, // whether stderr is sent as PROCESS_LOG events
+    pub(in crate::config) listener: Option<EventListener>, // set for [eventlistener:x] sections
+    pub(in crate::config) on_start: Option<String>, // shell command run when a process enters RUNNING
+    pub(in crate::config) on_exit: Option<String>,  // shell command run when a process enters EXITED
+    pub(in crate::config) on_backoff: Option<String>, // shell command run when a process enters BACKOFF
+    pub(in crate::config) on_fatal: Option<String>, // shell command run when a process enters FATAL
+    pub(in crate::config) hook_timeout: u32,        // seconds a hook may run before it is killed
     pub(in crate::config) processnames: HashSet<String>,
 }
 
@@ -40,11 +51,24 @@ impl Program {
         environment: Option<LinkedList<String>>,
         directory: Option<String>,
         umask: Option<u16>,
+        priority: Option<i32>,
+        stdout_events_enabled: Option<bool>,
+        stderr_events_enabled: Option<bool>,
+        listener: Option<EventListener>,
+        on_start: Option<String>,
+        on_exit: Option<String>,
+        on_backoff: Option<String>,
+        on_fatal: Option<String>,
+        hook_timeout: Option<u32>,
     ) -> Self {
         let numprocs = numprocs.unwrap_or(1);
-        let processnames = (1..numprocs)
-            .map(|i| format!("{}{}", programname, i))
-            .collect();
+        let processnames = if numprocs == 1 {
+            HashSet::from([programname.clone()])
+        } else {
+            (0..numprocs)
+                .map(|i| format!("{}{}", programname, i))
+                .collect()
+        };
         let stdout_logfile = stdout_logfile.unwrap_or_else(|| format!("{}.log", programname));
         let stderr_logfile = stderr_logfile.unwrap_or_else(|| format!("{}_err.log", programname));
 
@@ -64,13 +88,127 @@ impl Program {
             enviroment: environment,
             directory: directory,
             umask: umask,
+            priority: priority.unwrap_or(999),
+            stdout_events_enabled: stdout_events_enabled.unwrap_or(false),
+            stderr_events_enabled: stderr_events_enabled.unwrap_or(false),
+            listener,
+            on_start,
+            on_exit,
+            on_backoff,
+            on_fatal,
+            hook_timeout: hook_timeout.unwrap_or(10),
             processnames: processnames,
         }
     }
 
+    /// Moves relative logfiles into `dir`.
+    pub(in crate::config) fn place_logfiles(&mut self, dir: &str) {
+        for logfile in [&mut self.stdout_logfile, &mut self.stderr_logfile] {
+            if Path::new(logfile.as_str()).is_relative() {
+                *logfile = Path::new(dir).join(&logfile).to_string_lossy().into_owned();
+            }
+        }
+    }
+
     pub fn builder() -> ProgramBuilder {
         ProgramBuilder::new()
     }
+
+    pub fn programname(&self) -> &String {
+        &self.programname
+    }
+
+    pub fn command(&self) -> &LinkedList<String> {
+        &self.command
+    }
+
+    pub fn autostart(&self) -> bool {
+        self.autostart
+    }
+
+    pub fn autorestart(&self) -> program::AutoRestart {
+        self.autorestart
+    }
+
+    pub fn exitcodes(&self) -> &LinkedList<i32> {
+        &self.exitcodes
+    }
+
+    pub fn startsecs(&self) -> u8 {
+        self.startsecs
+    }
+
+    pub fn startretries(&self) -> u8 {
+        self.startretries
+    }
+
+    pub fn stopsignal(&self) -> i32 {
+        self.stopsignal
+    }
+
+    pub fn stopwaitsecs(&self) -> u32 {
+        self.stopwaitsecs
+    }
+
+    pub fn stdout_logfile(&self) -> &String {
+        &self.stdout_logfile
+    }
+
+    pub fn stderr_logfile(&self) -> &String {
+        &self.stderr_logfile
+    }
+
+    pub fn environment(&self) -> Option<&LinkedList<String>> {
+        self.enviroment.as_ref()
+    }
+
+    pub fn directory(&self) -> Option<&String> {
+        self.directory.as_ref()
+    }
+
+    pub fn umask(&self) -> Option<u16> {
+        self.umask
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    pub fn stdout_events_enabled(&self) -> bool {
+        self.stdout_events_enabled
+    }
+
+    pub fn stderr_events_enabled(&self) -> bool {
+        self.stderr_events_enabled
+    }
+
+    pub fn listener(&self) -> Option<&EventListener> {
+        self.listener.as_ref()
+    }
+
+    pub fn on_start(&self) -> Option<&String> {
+        self.on_start.as_ref()
+    }
+
+    pub fn on_exit(&self) -> Option<&String> {
+        self.on_exit.as_ref()
+    }
+
+    pub fn on_backoff(&self) -> Option<&String> {
+        self.on_backoff.as_ref()
+    }
+
+    pub fn on_fatal(&self) -> Option<&String> {
+        self.on_fatal.as_ref()
+    }
+
+    pub fn hook_timeout(&self) -> u32 {
+        self.hook_timeout
+    }
+
+    pub fn processnames(&self) -> &HashSet<String> {
+        &self.processnames
+    }
 }
 
 #[derive(Debug)]
@@ -90,6 +228,15 @@ pub struct ProgramBuilder {
     environment: Option<LinkedList<String>>,
     directory: Option<String>,
     umask: Option<u16>,
+    priority: Option<i32>,
+    stdout_events_enabled: Option<bool>,
+    stderr_events_enabled: Option<bool>,
+    listener: Option<EventListener>,
+    on_start: Option<String>,
+    on_exit: Option<String>,
+    on_backoff: Option<String>,
+    on_fatal: Option<String>,
+    hook_timeout: Option<u32>,
 }
 
 impl ProgramBuilder {
@@ -110,6 +257,15 @@ impl ProgramBuilder {
             environment: None,
             directory: None,
             umask: None,
+            priority: None,
+            stdout_events_enabled: None,
+            stderr_events_enabled: None,
+            listener: None,
+            on_start: None,
+            on_exit: None,
+            on_backoff: None,
+            on_fatal: None,
+            hook_timeout: None,
         }
     }
 
@@ -188,6 +344,51 @@ impl ProgramBuilder {
         self
     }
 
+    pub fn priority(self: &mut Self, priority: i32) -> &mut Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn stdout_events_enabled(self: &mut Self, enabled: bool) -> &mut Self {
+        self.stdout_events_enabled = Some(enabled);
+        self
+    }
+
+    pub fn stderr_events_enabled(self: &mut Self, enabled: bool) -> &mut Self {
+        self.stderr_events_enabled = Some(enabled);
+        self
+    }
+
+    pub fn listener(self: &mut Self, listener: EventListener) -> &mut Self {
+        self.listener = Some(listener);
+        self
+    }
+
+    pub fn on_start(self: &mut Self, command: String) -> &mut Self {
+        self.on_start = Some(command);
+        self
+    }
+
+    pub fn on_exit(self: &mut Self, command: String) -> &mut Self {
+        self.on_exit = Some(command);
+        self
+    }
+
+    pub fn on_backoff(self: &mut Self, command: String) -> &mut Self {
+        self.on_backoff = Some(command);
+        self
+    }
+
+    pub fn on_fatal(self: &mut Self, command: String) -> &mut Self {
+        self.on_fatal = Some(command);
+        self
+    }
+
+    pub fn hook_timeout(self: &mut Self, hook_timeout: u32) -> &mut Self {
+        self.hook_timeout = Some(hook_timeout);
+        self
+    }
+
     pub fn build(self) -> Result<Program, ProgramBuilderError> {
         let programname = self
             .programname
@@ -212,6 +413,15 @@ impl ProgramBuilder {
             self.environment,
             self.directory,
             self.umask,
+            self.priority,
+            self.stdout_events_enabled,
+            self.stderr_events_enabled,
+            self.listener,
+            self.on_start,
+            self.on_exit,
+            self.on_backoff,
+            self.on_fatal,
+            self.hook_timeout,
         ))
     }
 }
@@ -231,6 +441,14 @@ pub enum ProgramSection {
     Environment,
     Directory,
     Umask,
+    Priority,
+    StdoutEventsEnabled,
+    StderrEventsEnabled,
+    OnStart,
+    OnExit,
+    OnBackoff,
+    OnFatal,
+    HookTimeout,
 }
 
 impl ProgramSection {
@@ -250,6 +468,14 @@ impl ProgramSection {
             ProgramSection::Environment => program::ENVIRONMENT,
             ProgramSection::Directory => program::DIRECTORY,
             ProgramSection::Umask => program::UMASK,
+            ProgramSection::Priority => program::PRIORITY,
+            ProgramSection::StdoutEventsEnabled => program::STDOUT_EVENTS_ENABLED,
+            ProgramSection::StderrEventsEnabled => program::STDERR_EVENTS_ENABLED,
+            ProgramSection::OnStart => program::ON_START,
+            ProgramSection::OnExit => program::ON_EXIT,
+            ProgramSection::OnBackoff => program::ON_BACKOFF,
+            ProgramSection::OnFatal => program::ON_FATAL,
+            ProgramSection::HookTimeout => program::HOOK_TIMEOUT,
         }
     }
 
@@ -269,6 +495,14 @@ impl ProgramSection {
             program::ENVIRONMENT => Some(ProgramSection::Environment),
             program::DIRECTORY => Some(ProgramSection::Directory),
             program::UMASK => Some(ProgramSection::Umask),
+            program::PRIORITY => Some(ProgramSection::Priority),
+            program::STDOUT_EVENTS_ENABLED => Some(ProgramSection::StdoutEventsEnabled),
+            program::STDERR_EVENTS_ENABLED => Some(ProgramSection::StderrEventsEnabled),
+            program::ON_START => Some(ProgramSection::OnStart),
+            program::ON_EXIT => Some(ProgramSection::OnExit),
+            program::ON_BACKOFF => Some(ProgramSection::OnBackoff),
+            program::ON_FATAL => Some(ProgramSection::OnFatal),
+            program::HOOK_TIMEOUT => Some(ProgramSection::HookTimeout),
             _ => None,
         }
     }
@@ -290,6 +524,14 @@ pub mod program {
     pub const ENVIRONMENT: &str = "environment";
     pub const DIRECTORY: &str = "directory";
     pub const UMASK: &str = "umask";
+    pub const PRIORITY: &str = "priority";
+    pub const STDOUT_EVENTS_ENABLED: &str = "stdout_events_enabled";
+    pub const STDERR_EVENTS_ENABLED: &str = "stderr_events_enabled";
+    pub const ON_START: &str = "on_start";
+    pub const ON_EXIT: &str = "on_exit";
+    pub const ON_BACKOFF: &str = "on_backoff";
+    pub const ON_FATAL: &str = "on_fatal";
+    pub const HOOK_TIMEOUT: &str = "hook_timeout";
 
     #[derive(Debug, PartialEq, Eq, Copy, Clone)]
     pub enum AutoRestart {
@@ -6,6 +6,12 @@ pub struct RuntimeContext {
     pub logger: Logger,
 }
 
+impl RuntimeContext {
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
 impl Default for RuntimeContext {
     fn default() -> Self {
         RuntimeContext {
@@ -2,6 +2,10 @@
 pub enum Section {
     Taskmasterd,
     Program,
+    UnixHttpServer,
+    InetHttpServer,
+    EventListener,
+    Notify,
 }
 
 impl Section {
@@ -9,6 +13,10 @@ impl Section {
         match self {
             Section::Taskmasterd => section::TASKMASTERD,
             Section::Program => section::PROGRAM,
+            Section::UnixHttpServer => section::UNIX_HTTP_SERVER,
+            Section::InetHttpServer => section::INET_HTTP_SERVER,
+            Section::EventListener => section::EVENTLISTENER,
+            Section::Notify => section::NOTIFY,
         }
     }
 
@@ -16,6 +24,10 @@ impl Section {
         match s {
             section::TASKMASTERD => Some(Section::Taskmasterd),
             section::PROGRAM => Some(Section::Program),
+            section::UNIX_HTTP_SERVER => Some(Section::UnixHttpServer),
+            section::INET_HTTP_SERVER => Some(Section::InetHttpServer),
+            section::EVENTLISTENER => Some(Section::EventListener),
+            section::NOTIFY => Some(Section::Notify),
             _ => None,
         }
     }
@@ -24,4 +36,8 @@ impl Section {
 mod section {
     pub const TASKMASTERD: &str = "taskmasterd";
     pub const PROGRAM: &str = "program";
+    pub const UNIX_HTTP_SERVER: &str = "unix_http_server";
+    pub const INET_HTTP_SERVER: &str = "inet_http_server";
+    pub const EVENTLISTENER: &str = "eventlistener";
+    pub const NOTIFY: &str = "notify";
 }
@@ -6,6 +6,11 @@ use super::logger::LogLevel;
 pub struct Taskmasterd {
     pub(in crate::config) logfile: String,
     pub(in crate::config) loglevel: LogLevel,
+    pub(in crate::config) nodaemon: bool, // stay in the foreground
+    pub(in crate::config) pidfile: String,
+    pub(in crate::config) directory: Option<String>, // working directory once detached
+    pub(in crate::config) umask: u32,
+    pub(in crate::config) childlogdir: Option<String>, // where relative program logfiles go
 }
 
 impl Taskmasterd {
@@ -13,14 +18,48 @@ impl Taskmasterd {
         Taskmasterd {
             logfile: String::new(),
             loglevel: LogLevel::INFO,
+            nodaemon: false,
+            pidfile: taskmasterd::DEFAULT_PIDFILE.to_string(),
+            directory: None,
+            umask: 0o022,
+            childlogdir: None,
         }
     }
+
+    pub fn logfile(&self) -> &String {
+        &self.logfile
+    }
+
+    pub fn nodaemon(&self) -> bool {
+        self.nodaemon
+    }
+
+    pub fn pidfile(&self) -> &String {
+        &self.pidfile
+    }
+
+    pub fn directory(&self) -> Option<&String> {
+        self.directory.as_ref()
+    }
+
+    pub fn umask(&self) -> u32 {
+        self.umask
+    }
+
+    pub fn childlogdir(&self) -> Option<&String> {
+        self.childlogdir.as_ref()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TaskmasterdSection {
     Logfile,
     Loglevel,
+    Nodaemon,
+    Pidfile,
+    Directory,
+    Umask,
+    Childlogdir,
 }
 
 impl TaskmasterdSection {
@@ -28,6 +67,11 @@ impl TaskmasterdSection {
         match self {
             TaskmasterdSection::Logfile => taskmasterd::LOGFILE,
             TaskmasterdSection::Loglevel => taskmasterd::LOGLEVEL,
+            TaskmasterdSection::Nodaemon => taskmasterd::NODAEMON,
+            TaskmasterdSection::Pidfile => taskmasterd::PIDFILE,
+            TaskmasterdSection::Directory => taskmasterd::DIRECTORY,
+            TaskmasterdSection::Umask => taskmasterd::UMASK,
+            TaskmasterdSection::Childlogdir => taskmasterd::CHILDLOGDIR,
         }
     }
 
@@ -35,6 +79,11 @@ impl TaskmasterdSection {
         match s {
             taskmasterd::LOGFILE => Some(TaskmasterdSection::Logfile),
             taskmasterd::LOGLEVEL => Some(TaskmasterdSection::Loglevel),
+            taskmasterd::NODAEMON => Some(TaskmasterdSection::Nodaemon),
+            taskmasterd::PIDFILE => Some(TaskmasterdSection::Pidfile),
+            taskmasterd::DIRECTORY => Some(TaskmasterdSection::Directory),
+            taskmasterd::UMASK => Some(TaskmasterdSection::Umask),
+            taskmasterd::CHILDLOGDIR => Some(TaskmasterdSection::Childlogdir),
             _ => None,
         }
     }
@@ -44,4 +93,11 @@ pub mod taskmasterd {
     pub const TASKMASTERD: &str = "taskmasterd";
     pub const LOGFILE: &str = "logfile";
     pub const LOGLEVEL: &str = "loglevel";
+    pub const NODAEMON: &str = "nodaemon";
+    pub const PIDFILE: &str = "pidfile";
+    pub const DIRECTORY: &str = "directory";
+    pub const UMASK: &str = "umask";
+    pub const CHILDLOGDIR: &str = "childlogdir";
+
+    pub const DEFAULT_PIDFILE: &str = "/tmp/taskmasterd.pid";
 }
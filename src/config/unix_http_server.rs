@@ -0,0 +1,65 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnixHttpServer {
+    pub(in crate::config) file: String, // path of the control socket
+    pub(in crate::config) chmod: u32,   // permission bits of the socket
+    pub(in crate::config) chown: Option<(String, Option<String>)>, // user and optional group
+}
+
+impl Default for UnixHttpServer {
+    fn default() -> Self {
+        UnixHttpServer {
+            file: unix_http_server::DEFAULT_FILE.to_string(),
+            chmod: 0o700,
+            chown: None,
+        }
+    }
+}
+
+impl UnixHttpServer {
+    pub fn file(&self) -> &String {
+        &self.file
+    }
+
+    pub fn chmod(&self) -> u32 {
+        self.chmod
+    }
+
+    pub fn chown(&self) -> Option<&(String, Option<String>)> {
+        self.chown.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnixHttpServerSection {
+    File,
+    Chmod,
+    Chown,
+}
+
+impl UnixHttpServerSection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnixHttpServerSection::File => unix_http_server::FILE,
+            UnixHttpServerSection::Chmod => unix_http_server::CHMOD,
+            UnixHttpServerSection::Chown => unix_http_server::CHOWN,
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            unix_http_server::FILE => Some(UnixHttpServerSection::File),
+            unix_http_server::CHMOD => Some(UnixHttpServerSection::Chmod),
+            unix_http_server::CHOWN => Some(UnixHttpServerSection::Chown),
+            _ => None,
+        }
+    }
+}
+
+pub mod unix_http_server {
+    pub const UNIX_HTTP_SERVER: &str = "unix_http_server";
+    pub const FILE: &str = "file";
+    pub const CHMOD: &str = "chmod";
+    pub const CHOWN: &str = "chown";
+
+    pub const DEFAULT_FILE: &str = "/tmp/taskmaster.sock";
+}
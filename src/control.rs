@@ -0,0 +1,10 @@
+pub mod auth;
+pub mod client;
+pub mod connection;
+pub mod dashboard;
+pub mod http;
+pub mod metrics;
+pub mod protocol;
+pub mod rest;
+pub mod server;
+pub mod xmlrpc;
@@ -0,0 +1,106 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sha1::{Digest, Sha1};
+use subtle::ConstantTimeEq;
+
+use crate::config::inet_http_server::InetHttpServer;
+
+/// Marks a password stored as the hex SHA-1 of the real one.
+const SHA_PREFIX: &str = "{SHA}";
+
+/// The username and password every TCP client has to present.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    username: String,
+    password: String,
+}
+
+impl Credentials {
+    pub fn new(username: &str, password: &str) -> Self {
+        Credentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    /// The configured credentials, if any; they are set together or not at all.
+    pub fn from_config(config: &InetHttpServer) -> Option<Self> {
+        Some(Self::new(config.username()?, config.password()?))
+    }
+
+    /// Compares in constant time, so that how long it takes tells nothing
+    /// about how close a guess was.
+    pub fn verify(&self, username: &str, password: &str) -> bool {
+        let username = equal(username, &self.username);
+        let password = match self.password.strip_prefix(SHA_PREFIX) {
+            Some(digest) => equal(
+                &hex(&Sha1::digest(password.as_bytes())),
+                &digest.to_ascii_lowercase(),
+            ),
+            None => equal(password, &self.password),
+        };
+        username & password
+    }
+
+    /// Checks the value of an `Authorization: Basic` header.
+    pub fn verify_header(&self, header: &str) -> bool {
+        basic(header).is_some_and(|(username, password)| self.verify(&username, &password))
+    }
+}
+
+/// Decodes the username and password of an `Authorization: Basic` header.
+fn basic(header: &str) -> Option<(String, String)> {
+    let (scheme, encoded) = header.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (username, password) = decoded.split_once(':')?;
+    Some((username.to_string(), password.to_string()))
+}
+
+fn equal(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod credentials_tests {
+        use super::*;
+
+        #[test]
+        fn test_verify_plaintext() {
+            let credentials = Credentials::new("admin", "secret");
+            assert!(credentials.verify("admin", "secret"));
+            assert!(!credentials.verify("admin", "wrong"));
+            assert!(!credentials.verify("root", "secret"));
+        }
+
+        #[test]
+        fn test_verify_sha() {
+            // echo -n secret | sha1sum
+            let credentials =
+                Credentials::new("admin", "{SHA}e5e9fa1ba31ecd1ae84f75caaa474f3a663f05f4");
+            assert!(credentials.verify("admin", "secret"));
+            assert!(!credentials.verify("admin", "{SHA}e5e9fa1ba31ecd1ae84f75caaa474f3a663f05f4"));
+            let credentials =
+                Credentials::new("admin", "{SHA}E5E9FA1BA31ECD1AE84F75CAAA474F3A663F05F4");
+            assert!(credentials.verify("admin", "secret"));
+        }
+
+        #[test]
+        fn test_verify_header() {
+            let credentials = Credentials::new("admin", "secret");
+            // admin:secret
+            assert!(credentials.verify_header("Basic YWRtaW46c2VjcmV0"));
+            assert!(!credentials.verify_header("Bearer YWRtaW46c2VjcmV0"));
+            assert!(!credentials.verify_header("Basic not-base64"));
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::io::{self, BufReader};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::control::connection::{Address, Connection};
+use crate::control::protocol::{self, AvailInfo, Request, Response};
+use crate::errors::ExecError;
+use crate::exec::event::StateEvent;
+use crate::exec::process::ProcessInfo;
+use crate::exec::process_output::Stream;
+
+/// Talks to taskmasterd over its control socket or port, one connection per request.
+#[derive(Debug, Clone)]
+pub struct Client {
+    address: Address,
+    /// Sent first on every connection when set.
+    credentials: Option<(String, String)>,
+}
+
+/// A long running request: responses arrive on `responses` and `stream`
+/// can still be written to.
+pub struct Session<T> {
+    pub stream: Connection,
+    pub responses: Receiver<T>,
+}
+
+impl Client {
+    pub fn new(address: Address, credentials: Option<(String, String)>) -> Self {
+        Client {
+            address,
+            credentials,
+        }
+    }
+
+    fn connect(&self) -> Result<Connection, ExecError> {
+        let stream = self
+            .address
+            .connect()
+            .map_err(|e| ExecError::Unreachable(self.address.to_string(), e))?;
+        if let Some((username, password)) = &self.credentials {
+            let request = Request::Auth {
+                username: username.clone(),
+                password: password.clone(),
+            };
+            protocol::send(&stream, &request)?;
+            expect(Self::read(&mut BufReader::new(&stream))?, |r| {
+                matches!(r, Response::Done).then_some(())
+            })?;
+        }
+        Ok(stream)
+    }
+
+    fn call(&self, request: &Request) -> Result<Response, ExecError> {
+        let stream = self.connect()?;
+        protocol::send(&stream, request)?;
+        Self::read(&mut BufReader::new(&stream))
+    }
+
+    fn read(reader: &mut impl io::BufRead) -> Result<Response, ExecError> {
+        match protocol::receive(reader)? {
+            Some(Response::Error(e)) => Err(ExecError::Remote(e)),
+            Some(response) => Ok(response),
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "taskmasterd closed the connection",
+            )
+            .into()),
+        }
+    }
+
+    /// Sends a streaming request and hands every response after the first
+    /// acknowledgement to `convert`, until it returns `None` or the daemon hangs up.
+    fn open<T: Send + 'static>(
+        &self,
+        request: &Request,
+        convert: fn(Response) -> Option<T>,
+    ) -> Result<Session<T>, ExecError> {
+        let stream = self.connect()?;
+        protocol::send(&stream, request)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        expect(Self::read(&mut reader)?, |r| {
+            matches!(r, Response::Done).then_some(())
+        })?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(Some(response)) = protocol::receive(&mut reader) {
+                let Some(value) = convert(response) else {
+                    break;
+                };
+                if tx.send(value).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Session {
+            stream,
+            responses: rx,
+        })
+    }
+
+    pub fn resolve(&self, targets: &[String]) -> Result<Vec<(String, String)>, ExecError> {
+        let request = Request::Resolve {
+            targets: targets.to_vec(),
+        };
+        expect(self.call(&request)?, |r| match r {
+            Response::Targets(targets) => Some(targets),
+            _ => None,
+        })
+    }
+
+    pub fn infos(&self, targets: &[(String, String)]) -> Result<Vec<ProcessInfo>, ExecError> {
+        let request = Request::Info {
+            targets: targets.to_vec(),
+        };
+        expect(self.call(&request)?, |r| match r {
+            Response::Infos(infos) => Some(infos),
+            _ => None,
+        })
+    }
+
+    pub fn start(&self, group: &str, name: &str) -> Result<(), ExecError> {
+        self.done(Request::Start {
+            group: group.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    pub fn stop(&self, group: &str, name: &str) -> Result<(), ExecError> {
+        self.done(Request::Stop {
+            group: group.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    pub fn signal(&self, group: &str, name: &str, signum: i32) -> Result<(), ExecError> {
+        self.done(Request::Signal {
+            group: group.to_string(),
+            name: name.to_string(),
+            signum,
+        })
+    }
+
+    pub fn clear(&self, group: &str, name: &str) -> Result<(), ExecError> {
+        self.done(Request::Clear {
+            group: group.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    fn done(&self, request: Request) -> Result<(), ExecError> {
+        expect(self.call(&request)?, |r| {
+            matches!(r, Response::Done).then_some(())
+        })
+    }
+
+    pub fn avail(&self) -> Result<Vec<AvailInfo>, ExecError> {
+        expect(self.call(&Request::Avail)?, |r| match r {
+            Response::Avail(avail) => Some(avail),
+            _ => None,
+        })
+    }
+
+    /// The pid of taskmasterd itself.
+    pub fn pid(&self) -> Result<u32, ExecError> {
+        expect(self.call(&Request::Pid)?, |r| match r {
+            Response::Pid(pid) => Some(pid),
+            _ => None,
+        })
+    }
+
+    pub fn completion_names(&self) -> Result<Vec<String>, ExecError> {
+        expect(self.call(&Request::CompletionNames)?, |r| match r {
+            Response::Names(names) => Some(names),
+            _ => None,
+        })
+    }
+
+    /// Stops every process and taskmasterd; returns the processes that had to be SIGKILLed.
+    pub fn shutdown(&self) -> Result<Vec<(String, String)>, ExecError> {
+        expect(self.call(&Request::Shutdown)?, |r| match r {
+            Response::Killed(killed) => Some(killed),
+            _ => None,
+        })
+    }
+
+    pub fn events(&self) -> Result<Session<StateEvent>, ExecError> {
+        self.open(&Request::Events, |r| match r {
+            Response::Event(event) => Some(event),
+            _ => None,
+        })
+    }
+
+    /// Streams the output of a running process; see `send_input`.
+    pub fn attach(&self, group: &str, name: &str) -> Result<Session<(Stream, Vec<u8>)>, ExecError> {
+        let request = Request::Attach {
+            group: group.to_string(),
+            name: name.to_string(),
+        };
+        self.open(&request, |r| match r {
+            Response::Output { stream, data } => Some((stream, data)),
+            _ => None,
+        })
+    }
+
+    pub fn send_input(session: &Session<(Stream, Vec<u8>)>, data: &[u8]) -> Result<(), ExecError> {
+        let request = Request::Input {
+            data: data.to_vec(),
+        };
+        Ok(protocol::send(&session.stream, &request)?)
+    }
+}
+
+fn expect<T>(
+    response: Response,
+    convert: impl FnOnce(Response) -> Option<T>,
+) -> Result<T, ExecError> {
+    convert(response).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected response from taskmasterd",
+        )
+        .into()
+    })
+}
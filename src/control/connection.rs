@@ -0,0 +1,175 @@
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Where taskmasterd listens for control clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Address {
+    Unix(PathBuf),
+    /// `host:port` of an `[inet_http_server]`.
+    Inet(String),
+}
+
+impl Address {
+    /// Accepts `unix:///path`, `http://host:port`, `host:port` or a plain socket path.
+    pub fn parse(s: &str) -> Self {
+        if let Some(path) = s.strip_prefix("unix://") {
+            return Address::Unix(PathBuf::from(path));
+        }
+        if let Some(address) = s.strip_prefix("http://") {
+            return Address::Inet(address.trim_end_matches('/').to_string());
+        }
+        match s.rsplit_once(':') {
+            Some((_, port)) if !s.contains('/') && port.parse::<u16>().is_ok() => {
+                Address::Inet(s.to_string())
+            }
+            _ => Address::Unix(PathBuf::from(s)),
+        }
+    }
+
+    pub fn connect(&self) -> io::Result<Connection> {
+        match self {
+            Address::Unix(path) => UnixStream::connect(path).map(Connection::Unix),
+            Address::Inet(address) => TcpStream::connect(address).map(Connection::Tcp),
+        }
+    }
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Address::Unix(path) => write!(f, "{}", path.display()),
+            Address::Inet(address) => write!(f, "http://{}", address),
+        }
+    }
+}
+
+/// A control connection, whichever listener it came through.
+#[derive(Debug)]
+pub enum Connection {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Connection {
+    pub fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Connection::Unix(stream) => stream.try_clone().map(Connection::Unix),
+            Connection::Tcp(stream) => stream.try_clone().map(Connection::Tcp),
+        }
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Connection::Unix(stream) => stream.set_nonblocking(nonblocking),
+            Connection::Tcp(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Connection::Unix(stream) => stream.set_read_timeout(timeout),
+            Connection::Tcp(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+
+    /// The first byte the peer sent, left in place for the next read.
+    pub fn peek(&self) -> io::Result<Option<u8>> {
+        let mut first = 0u8;
+        let n = unsafe {
+            libc::recv(
+                self.as_raw_fd(),
+                (&mut first as *mut u8).cast(),
+                1,
+                libc::MSG_PEEK,
+            )
+        };
+        match n {
+            n if n < 0 => Err(io::Error::last_os_error()),
+            0 => Ok(None),
+            _ => Ok(Some(first)),
+        }
+    }
+}
+
+impl AsRawFd for Connection {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Connection::Unix(stream) => stream.as_raw_fd(),
+            Connection::Tcp(stream) => stream.as_raw_fd(),
+        }
+    }
+}
+
+impl Read for &Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Connection::Unix(stream) => (&*stream).read(buf),
+            Connection::Tcp(stream) => (&*stream).read(buf),
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+}
+
+impl Write for &Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Unix(stream) => (&*stream).write(buf),
+            Connection::Tcp(stream) => (&*stream).write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Unix(stream) => (&*stream).flush(),
+            Connection::Tcp(stream) => (&*stream).flush(),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod address_tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_unix() {
+            let expected = Address::Unix(PathBuf::from("/tmp/taskmaster.sock"));
+            assert_eq!(Address::parse("/tmp/taskmaster.sock"), expected);
+            assert_eq!(Address::parse("unix:///tmp/taskmaster.sock"), expected);
+        }
+
+        #[test]
+        fn test_parse_inet() {
+            let expected = Address::Inet("127.0.0.1:9001".to_string());
+            assert_eq!(Address::parse("127.0.0.1:9001"), expected);
+            assert_eq!(Address::parse("http://127.0.0.1:9001/"), expected);
+        }
+
+        #[test]
+        fn test_parse_relative_path_with_colon() {
+            let expected = Address::Unix(PathBuf::from("run/tm:sock"));
+            assert_eq!(Address::parse("run/tm:sock"), expected);
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use dxr_server::axum::Router;
+use dxr_server::axum::response::Html;
+use dxr_server::axum::routing::get;
+
+/// The page is static: it polls the REST API for everything it shows, so it
+/// is protected by whatever protects the API.
+const PAGE: &str = include_str!("dashboard.html");
+
+pub fn route() -> Router {
+    Router::new().route("/", get(page))
+}
+
+async fn page() -> Html<&'static str> {
+    Html(PAGE)
+}
@@ -0,0 +1,93 @@
+use std::io;
+use std::sync::Arc;
+
+use dxr_server::axum::Router;
+use dxr_server::axum::extract::Request;
+use dxr_server::axum::http::{StatusCode, header};
+use dxr_server::axum::middleware::{self, Next};
+use dxr_server::axum::response::{IntoResponse, Response};
+use hyper::server::conn::http1;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::config::logger::Logger;
+use crate::control::auth::Credentials;
+use crate::control::connection::Connection;
+use crate::control::dashboard;
+use crate::control::metrics;
+use crate::control::rest;
+use crate::control::server::Daemon;
+use crate::control::xmlrpc;
+
+/// Every HTTP endpoint of a control listener, behind basic authentication
+/// when `credentials` are given.
+pub fn router(daemon: &Arc<Daemon>, credentials: Option<Credentials>) -> Router {
+    let router = xmlrpc::route(daemon)
+        .merge(rest::route(daemon))
+        .merge(metrics::route(daemon))
+        .merge(dashboard::route());
+    match credentials {
+        Some(credentials) => {
+            let logger = daemon.manager.lock().unwrap().logger().clone();
+            protect(router, credentials, logger)
+        }
+        None => router,
+    }
+}
+
+fn protect(router: Router, credentials: Credentials, logger: Logger) -> Router {
+    router.layer(middleware::from_fn(move |request: Request, next: Next| {
+        let authorized = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| credentials.verify_header(value));
+        if !authorized {
+            logger.warn("HTTP client failed to authenticate");
+        }
+        async move {
+            match authorized {
+                true => next.run(request).await,
+                false => unauthorized(),
+            }
+        }
+    }))
+}
+
+fn unauthorized() -> Response {
+    let challenge = [(header::WWW_AUTHENTICATE, "Basic realm=\"taskmasterd\"")];
+    (StatusCode::UNAUTHORIZED, challenge).into_response()
+}
+
+/// Serves HTTP on an accepted connection until the client hangs up.
+pub fn serve(daemon: &Arc<Daemon>, stream: Connection, router: Router) -> io::Result<()> {
+    stream.set_nonblocking(true)?;
+    let logger = daemon.manager.lock().unwrap().logger().clone();
+    daemon.runtime.spawn(async move {
+        let result = match stream {
+            Connection::Unix(stream) => match tokio::net::UnixStream::from_std(stream) {
+                Ok(stream) => serve_connection(stream, router).await,
+                Err(e) => return logger.error(&format!("control socket: {}", e)),
+            },
+            Connection::Tcp(stream) => match tokio::net::TcpStream::from_std(stream) {
+                Ok(stream) => serve_connection(stream, router).await,
+                Err(e) => return logger.error(&format!("control socket: {}", e)),
+            },
+        };
+        if let Err(e) = result {
+            logger.debug(&format!("http client: {}", e));
+        }
+    });
+    Ok(())
+}
+
+async fn serve_connection<S>(stream: S, router: Router) -> hyper::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let service = TowerToHyperService::new(router);
+    http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), service)
+        .await
+}
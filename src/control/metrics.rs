@@ -0,0 +1,195 @@
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use dxr_server::axum::Router;
+use dxr_server::axum::extract::State;
+use dxr_server::axum::http::{StatusCode, header};
+use dxr_server::axum::response::{IntoResponse, Response};
+use dxr_server::axum::routing::get;
+
+use crate::control::server::Daemon;
+use crate::exec::process::ProcessInfo;
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Reads a metric off a process, if it has a value at all.
+type Sample = fn(&ProcessInfo) -> Option<f64>;
+
+/// Per-process metrics: name, help, type and value.
+const PROCESS_METRICS: [(&str, &str, &str, Sample); 7] = [
+    (
+        "taskmaster_process_state",
+        "Process state, as the supervisord state code.",
+        "gauge",
+        |info| Some(info.state.code() as f64),
+    ),
+    (
+        "taskmaster_process_up",
+        "Whether the process is running.",
+        "gauge",
+        |info| Some(info.state.is_running() as u8 as f64),
+    ),
+    (
+        "taskmaster_process_uptime_seconds",
+        "Seconds since the process was started.",
+        "gauge",
+        |info| Some(info.uptime as f64),
+    ),
+    (
+        "taskmaster_process_restarts_total",
+        "Times the process was started again.",
+        "counter",
+        |info| Some(info.restarts as f64),
+    ),
+    (
+        "taskmaster_process_exit_code",
+        "Exit code of the last exit, negative for a signal.",
+        "gauge",
+        |info| info.exitstatus.map(f64::from),
+    ),
+    (
+        "taskmaster_process_cpu_seconds",
+        "CPU seconds used by the running process.",
+        "gauge",
+        |info| info.cpu,
+    ),
+    (
+        "taskmaster_process_resident_memory_bytes",
+        "Resident set size of the running process.",
+        "gauge",
+        |info| info.rss.map(|rss| rss as f64),
+    ),
+];
+
+pub fn route(daemon: &Arc<Daemon>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(Arc::clone(daemon))
+}
+
+async fn metrics(State(daemon): State<Arc<Daemon>>) -> Response {
+    // it locks the ProcessManager and reads /proc for every process
+    match tokio::task::spawn_blocking(move || collect(&daemon)).await {
+        Ok(body) => ([(header::CONTENT_TYPE, CONTENT_TYPE)], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+fn collect(daemon: &Daemon) -> String {
+    let (infos, spawn_failures) = {
+        let manager = daemon.manager.lock().unwrap();
+        let infos = manager
+            .resolve_targets("all")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(group, name)| Some(manager.get_process(group, name)?.info(group)))
+            .collect::<Vec<_>>();
+        (infos, manager.spawn_failures())
+    };
+    let reloads = daemon.reloads.load(Ordering::Relaxed);
+    render(&infos, spawn_failures, reloads)
+}
+
+/// The Prometheus text exposition of the processes and daemon counters.
+fn render(infos: &[ProcessInfo], spawn_failures: u64, reloads: u64) -> String {
+    let mut out = String::new();
+    for (metric, help, kind, value) in PROCESS_METRICS {
+        let _ = writeln!(out, "# HELP {} {}", metric, help);
+        let _ = writeln!(out, "# TYPE {} {}", metric, kind);
+        for info in infos {
+            if let Some(value) = value(info) {
+                let _ = writeln!(
+                    out,
+                    "{}{{group=\"{}\",name=\"{}\"}} {}",
+                    metric,
+                    escape(&info.group),
+                    escape(&info.name),
+                    value
+                );
+            }
+        }
+    }
+    let counters = [
+        (
+            "taskmaster_spawn_failures_total",
+            "Starts that failed or exited too quickly.",
+            spawn_failures,
+        ),
+        (
+            "taskmaster_config_reloads_total",
+            "Times the configuration was read again.",
+            reloads,
+        ),
+    ];
+    for (metric, help, value) in counters {
+        let _ = writeln!(out, "# HELP {} {}", metric, help);
+        let _ = writeln!(out, "# TYPE {} counter", metric);
+        let _ = writeln!(out, "{} {}", metric, value);
+    }
+    out
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::process::ProcessState;
+
+    mod render_tests {
+        use super::*;
+
+        fn info(name: &str, state: ProcessState) -> ProcessInfo {
+            ProcessInfo {
+                name: name.to_string(),
+                group: "web".to_string(),
+                state,
+                pid: 0,
+                uptime: 12,
+                restarts: 3,
+                exitstatus: None,
+                description: String::new(),
+                cpu: None,
+                rss: None,
+            }
+        }
+
+        #[test]
+        fn test_render_process_metrics() {
+            let out = render(&[info("web_0", ProcessState::RUNNING)], 0, 0);
+            assert!(out.contains("taskmaster_process_state{group=\"web\",name=\"web_0\"} 20\n"));
+            assert!(out.contains("taskmaster_process_up{group=\"web\",name=\"web_0\"} 1\n"));
+            assert!(
+                out.contains("taskmaster_process_restarts_total{group=\"web\",name=\"web_0\"} 3\n")
+            );
+            assert!(out.contains("# TYPE taskmaster_process_up gauge\n"));
+            assert!(out.contains("# TYPE taskmaster_process_restarts_total counter\n"));
+        }
+
+        #[test]
+        fn test_render_skips_missing_values() {
+            let out = render(&[info("web_0", ProcessState::STOPPED)], 0, 0);
+            assert!(!out.contains("taskmaster_process_exit_code{"));
+            assert!(!out.contains("taskmaster_process_cpu_seconds{"));
+        }
+
+        #[test]
+        fn test_render_counters() {
+            let out = render(&[], 4, 2);
+            assert!(out.contains("# TYPE taskmaster_spawn_failures_total counter\n"));
+            assert!(out.contains("taskmaster_spawn_failures_total 4\n"));
+            assert!(out.contains("taskmaster_config_reloads_total 2\n"));
+        }
+
+        #[test]
+        fn test_escape_label() {
+            assert_eq!(escape("a\"b\\c\n"), "a\\\"b\\\\c\\n");
+        }
+    }
+}
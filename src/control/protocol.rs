@@ -0,0 +1,158 @@
+use std::io::{self, BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ExecError;
+use crate::exec::event::StateEvent;
+use crate::exec::process::ProcessInfo;
+use crate::exec::process_output::Stream;
+
+/// What taskmasterctl asks of taskmasterd, one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum Request {
+    /// Opens a session on a listener that requires credentials.
+    Auth {
+        username: String,
+        password: String,
+    },
+    Resolve {
+        targets: Vec<String>,
+    },
+    Info {
+        targets: Vec<(String, String)>,
+    },
+    Start {
+        group: String,
+        name: String,
+    },
+    Stop {
+        group: String,
+        name: String,
+    },
+    Signal {
+        group: String,
+        name: String,
+        signum: i32,
+    },
+    Clear {
+        group: String,
+        name: String,
+    },
+    Avail,
+    Pid,
+    CompletionNames,
+    Shutdown,
+    /// Streams every state change until the client hangs up.
+    Events,
+    /// Streams the output of a process; `Input` requests feed its stdin.
+    Attach {
+        group: String,
+        name: String,
+    },
+    Input {
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "response", content = "value", rename_all = "snake_case")]
+pub enum Response {
+    Done,
+    Targets(Vec<(String, String)>),
+    Infos(Vec<ProcessInfo>),
+    Avail(Vec<AvailInfo>),
+    Pid(u32),
+    Names(Vec<String>),
+    Killed(Vec<(String, String)>),
+    Event(StateEvent),
+    Output { stream: Stream, data: Vec<u8> },
+    Error(RemoteError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailInfo {
+    pub group: String,
+    pub name: String,
+    pub autostart: bool,
+}
+
+/// An `ExecError` raised by taskmasterd, carried over to the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteError {
+    pub message: String,
+    pub reason: String,
+    pub code: i32,
+}
+
+impl From<&ExecError> for RemoteError {
+    fn from(e: &ExecError) -> Self {
+        RemoteError {
+            message: e.to_string(),
+            reason: e.reason(),
+            code: e.exit_code(),
+        }
+    }
+}
+
+pub fn send<T: Serialize>(mut writer: impl Write, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line)
+}
+
+/// Reads the next message, or `None` once the peer has hung up.
+pub fn receive<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod framing_tests {
+        use super::*;
+
+        #[test]
+        fn test_request_round_trip() {
+            let request = Request::Signal {
+                group: "web".to_string(),
+                name: "web_0".to_string(),
+                signum: 1,
+            };
+            let mut buf = Vec::new();
+            send(&mut buf, &request).unwrap();
+            send(&mut buf, &Request::Pid).unwrap();
+            let mut reader = buf.as_slice();
+            assert_eq!(receive::<Request>(&mut reader).unwrap(), Some(request));
+            assert_eq!(receive::<Request>(&mut reader).unwrap(), Some(Request::Pid));
+            assert_eq!(receive::<Request>(&mut reader).unwrap(), None);
+        }
+
+        #[test]
+        fn test_error_keeps_code_and_reason() {
+            let error = RemoteError::from(&ExecError::NotRunning("web".to_string()));
+            let mut buf = Vec::new();
+            send(&mut buf, &Response::Error(error)).unwrap();
+            let Some(Response::Error(error)) = receive(&mut buf.as_slice()).unwrap() else {
+                panic!("expected an error response");
+            };
+            assert_eq!(error.message, "web: not running.");
+            assert_eq!(error.reason, "not running");
+            assert_eq!(error.code, 3);
+        }
+
+        #[test]
+        fn test_receive_garbage() {
+            let result = receive::<Request>(&mut "not json\n".as_bytes());
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::thread;
+
+use dxr_server::axum::extract::{Path, Query, Request, State};
+use dxr_server::axum::http::{HeaderMap, StatusCode, header};
+use dxr_server::axum::middleware::{self, Next};
+use dxr_server::axum::response::{IntoResponse, Response};
+use dxr_server::axum::routing::{get, post};
+use dxr_server::axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::control::protocol::RemoteError;
+use crate::control::server::Daemon;
+use crate::errors::ExecError;
+use crate::exec::process::{ProcessInfo, ProcessState};
+use crate::exec::process_manager::TICK;
+use crate::exec::signal;
+
+/// The most log bytes one request returns; ask again from the returned offset for more.
+const MAX_LOG_CHUNK: u64 = 64 * 1024;
+
+/// The header requests acting on processes must carry, whatever its value.
+pub const REQUESTED_BY: &str = "x-taskmaster";
+
+/// A JSON view of the processes, for clients that would rather not speak XML-RPC.
+/// `{name}` is a process name or `group:name`.
+pub fn route(daemon: &Arc<Daemon>) -> Router {
+    Router::new()
+        .route("/processes", get(list))
+        .route("/processes/:name", get(show))
+        .route("/processes/:name/log", get(log))
+        .merge(actions())
+        .with_state(Arc::clone(daemon))
+}
+
+fn actions() -> Router<Arc<Daemon>> {
+    Router::new()
+        .route("/processes/:name/start", post(start))
+        .route("/processes/:name/stop", post(stop))
+        .route("/processes/:name/restart", post(restart))
+        .route("/processes/:name/signal", post(signal))
+        .route_layer(middleware::from_fn(same_origin))
+}
+
+/// Keeps the web pages the operator visits from acting on the processes: a
+/// browser sends a custom header to another site only once a preflight
+/// request allowed it, which taskmasterd never does, and names the page a
+/// request comes from in `Origin`.
+async fn same_origin(request: Request, next: Next) -> Response {
+    match check_origin(request.headers()) {
+        Ok(()) => next.run(request).await,
+        Err(e) => ApiError(e).into_response(),
+    }
+}
+
+fn check_origin(headers: &HeaderMap) -> Result<(), ExecError> {
+    if !headers.contains_key(REQUESTED_BY) {
+        return Err(ExecError::CrossSite(format!("no {} header", REQUESTED_BY)));
+    }
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return Ok(());
+    };
+    let origin = origin.to_str().unwrap_or_default();
+    let host = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or_default();
+    match origin.split_once("://") {
+        Some((_, authority)) if !host.is_empty() && authority.eq_ignore_ascii_case(host) => Ok(()),
+        _ => Err(ExecError::CrossSite(format!("{} is not {}", origin, host))),
+    }
+}
+
+/// An `ExecError` as a status code and the same body taskmasterctl receives.
+struct ApiError(ExecError);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            ExecError::ProcessNameNotFound(_) => StatusCode::NOT_FOUND,
+            ExecError::AlreadyStarted(_) | ExecError::NotRunning(_) => StatusCode::CONFLICT,
+            ExecError::InvalidArgs | ExecError::InvalidSignal(_) | ExecError::AmbiguousName(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            ExecError::CrossSite(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(RemoteError::from(&self.0))).into_response()
+    }
+}
+
+impl From<ExecError> for ApiError {
+    fn from(e: ExecError) -> Self {
+        ApiError(e)
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Runs `f` off the async workers: it locks the ProcessManager and may sleep.
+async fn blocking<T: Send + 'static>(
+    daemon: Arc<Daemon>,
+    f: impl FnOnce(&Daemon) -> Result<T, ExecError> + Send + 'static,
+) -> ApiResult<T> {
+    tokio::task::spawn_blocking(move || f(&daemon))
+        .await
+        .map_err(|e| ExecError::Io(std::io::Error::other(e)))?
+        .map(Json)
+        .map_err(ApiError)
+}
+
+/// The single process `name` refers to; a pattern matching several is
+/// refused rather than applied to an arbitrary one.
+fn resolve(daemon: &Daemon, name: &str) -> Result<(String, String), ExecError> {
+    let manager = daemon.manager.lock().unwrap();
+    match manager.resolve_targets(name)?.as_slice() {
+        [target] => Ok(target.clone()),
+        _ => Err(ExecError::AmbiguousName(name.to_string())),
+    }
+}
+
+fn info(daemon: &Daemon, (group, name): &(String, String)) -> Result<ProcessInfo, ExecError> {
+    let manager = daemon.manager.lock().unwrap();
+    let process = manager
+        .get_process(group, name)
+        .ok_or_else(|| ExecError::ProcessNameNotFound(name.clone()))?;
+    Ok(process.info(group))
+}
+
+async fn list(State(daemon): State<Arc<Daemon>>) -> ApiResult<Vec<ProcessInfo>> {
+    blocking(daemon, |daemon| {
+        let targets = daemon
+            .manager
+            .lock()
+            .unwrap()
+            .resolve_targets("all")
+            .unwrap_or_default();
+        targets.iter().map(|target| info(daemon, target)).collect()
+    })
+    .await
+}
+
+async fn show(
+    State(daemon): State<Arc<Daemon>>,
+    Path(name): Path<String>,
+) -> ApiResult<ProcessInfo> {
+    blocking(daemon, move |daemon| info(daemon, &resolve(daemon, &name)?)).await
+}
+
+async fn start(
+    State(daemon): State<Arc<Daemon>>,
+    Path(name): Path<String>,
+) -> ApiResult<ProcessInfo> {
+    blocking(daemon, move |daemon| {
+        let target = resolve(daemon, &name)?;
+        daemon.manager.lock().unwrap().start(&target.0, &target.1)?;
+        info(daemon, &target)
+    })
+    .await
+}
+
+async fn stop(
+    State(daemon): State<Arc<Daemon>>,
+    Path(name): Path<String>,
+) -> ApiResult<ProcessInfo> {
+    blocking(daemon, move |daemon| {
+        let target = resolve(daemon, &name)?;
+        daemon.manager.lock().unwrap().stop(&target.0, &target.1)?;
+        info(daemon, &target)
+    })
+    .await
+}
+
+/// Stops the process if it runs, waits for it to exit and starts it again.
+async fn restart(
+    State(daemon): State<Arc<Daemon>>,
+    Path(name): Path<String>,
+) -> ApiResult<ProcessInfo> {
+    blocking(daemon, move |daemon| {
+        let target = resolve(daemon, &name)?;
+        match daemon.manager.lock().unwrap().stop(&target.0, &target.1) {
+            Ok(()) | Err(ExecError::NotRunning(_)) => {}
+            Err(e) => return Err(e),
+        }
+        while info(daemon, &target)?.state == ProcessState::STOPPING {
+            thread::sleep(TICK);
+        }
+        daemon.manager.lock().unwrap().start(&target.0, &target.1)?;
+        info(daemon, &target)
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+struct SignalQuery {
+    signal: String,
+}
+
+async fn signal(
+    State(daemon): State<Arc<Daemon>>,
+    Path(name): Path<String>,
+    Query(query): Query<SignalQuery>,
+) -> ApiResult<ProcessInfo> {
+    blocking(daemon, move |daemon| {
+        let signum = signal::from_str(&query.signal)
+            .ok_or_else(|| ExecError::InvalidSignal(query.signal.clone()))?;
+        let target = resolve(daemon, &name)?;
+        {
+            let manager = daemon.manager.lock().unwrap();
+            let process = manager
+                .get_process(&target.0, &target.1)
+                .ok_or_else(|| ExecError::ProcessNameNotFound(name.clone()))?;
+            process.signal(signum, manager.logger())?;
+        }
+        info(daemon, &target)
+    })
+    .await
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LogStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogQuery {
+    #[serde(default)]
+    offset: u64,
+    /// Only the last `tail` bytes, whatever the offset.
+    tail: Option<u64>,
+    #[serde(default)]
+    stream: LogStream,
+}
+
+#[derive(Debug, Serialize)]
+struct LogChunk {
+    data: String,
+    /// Where the next request should continue.
+    offset: u64,
+}
+
+/// The log bytes from `offset` on, or the last `tail` ones, at most
+/// `MAX_LOG_CHUNK` of them. A log that shrank since, because it was cleared,
+/// is read again from the start.
+fn log_range(size: u64, offset: u64, tail: Option<u64>) -> (u64, u64) {
+    let start = match tail {
+        Some(tail) => size - tail.min(size),
+        None if offset > size => 0,
+        None => offset,
+    };
+    (start, (size - start).min(MAX_LOG_CHUNK))
+}
+
+async fn log(
+    State(daemon): State<Arc<Daemon>>,
+    Path(name): Path<String>,
+    Query(query): Query<LogQuery>,
+) -> ApiResult<LogChunk> {
+    blocking(daemon, move |daemon| {
+        let (group, _) = resolve(daemon, &name)?;
+        let path = {
+            let manager = daemon.manager.lock().unwrap();
+            let program = manager
+                .get_program(&group)
+                .ok_or_else(|| ExecError::ProcessNameNotFound(group.clone()))?;
+            match query.stream {
+                LogStream::Stdout => program.stdout_logfile().clone(),
+                LogStream::Stderr => program.stderr_logfile().clone(),
+            }
+        };
+        let mut file = match File::open(&path) {
+            Ok(file) => file,
+            // nothing was logged yet
+            Err(_) => {
+                return Ok(LogChunk {
+                    data: String::new(),
+                    offset: 0,
+                });
+            }
+        };
+        let (start, length) = log_range(file.metadata()?.len(), query.offset, query.tail);
+        let mut data = Vec::new();
+        file.seek(SeekFrom::Start(start))?;
+        file.take(length).read_to_end(&mut data)?;
+        Ok(LogChunk {
+            data: String::from_utf8_lossy(&data).into_owned(),
+            offset: start + data.len() as u64,
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod check_origin_tests {
+        use super::*;
+
+        fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(*name, value.parse().unwrap());
+            }
+            headers
+        }
+
+        #[test]
+        fn test_check_origin_requires_header() {
+            assert!(check_origin(&headers(&[])).is_err());
+            assert!(check_origin(&headers(&[(REQUESTED_BY, "1")])).is_ok());
+        }
+
+        #[test]
+        fn test_check_origin_same_host() {
+            let same = [
+                (REQUESTED_BY, "1"),
+                ("host", "127.0.0.1:9001"),
+                ("origin", "http://127.0.0.1:9001"),
+            ];
+            assert!(check_origin(&headers(&same)).is_ok());
+        }
+
+        #[test]
+        fn test_check_origin_other_site() {
+            let other = [
+                (REQUESTED_BY, "1"),
+                ("host", "127.0.0.1:9001"),
+                ("origin", "https://example.com"),
+            ];
+            assert!(check_origin(&headers(&other)).is_err());
+            let opaque = [
+                (REQUESTED_BY, "1"),
+                ("host", "127.0.0.1:9001"),
+                ("origin", "null"),
+            ];
+            assert!(check_origin(&headers(&opaque)).is_err());
+        }
+    }
+
+    mod log_range_tests {
+        use super::*;
+
+        #[test]
+        fn test_log_range_from_offset() {
+            assert_eq!(log_range(100, 0, None), (0, 100));
+            assert_eq!(log_range(100, 40, None), (40, 60));
+            assert_eq!(log_range(100, 100, None), (100, 0));
+        }
+
+        #[test]
+        fn test_log_range_capped() {
+            assert_eq!(log_range(MAX_LOG_CHUNK * 3, 0, None), (0, MAX_LOG_CHUNK));
+        }
+
+        #[test]
+        fn test_log_range_tail() {
+            assert_eq!(log_range(100, 0, Some(30)), (70, 30));
+            assert_eq!(log_range(10, 0, Some(30)), (0, 10));
+        }
+
+        #[test]
+        fn test_log_range_after_truncation() {
+            assert_eq!(log_range(10, 100, None), (0, 10));
+        }
+    }
+}
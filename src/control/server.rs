@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::fs::{self, Permissions};
+use std::io::{self, BufReader, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::AtomicU64;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use nix::unistd::{Group, User};
+use tokio::runtime::Runtime;
+
+use crate::config::inet_http_server::InetHttpServer;
+use crate::config::program::Program;
+use crate::config::unix_http_server::UnixHttpServer;
+use crate::control::auth::Credentials;
+use crate::control::connection::Connection;
+use crate::control::http;
+use crate::control::protocol::{self, AvailInfo, RemoteError, Request, Response};
+use crate::errors::ExecError;
+use crate::exec::process_manager::ProcessManager;
+
+/// How long a client may take to send its first byte, which tells the
+/// protocols apart, before it is hung up on.
+const FIRST_BYTE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the control connections share with the rest of taskmasterd.
+pub struct Daemon {
+    pub manager: Arc<Mutex<ProcessManager>>,
+    /// Asks the main thread for an orderly shutdown.
+    pub exit: Sender<()>,
+    /// The configuration file that `reloadConfig` reads again.
+    pub config_file: Option<String>,
+    /// Programs read by the last `reloadConfig`, waiting to be added.
+    pub reloaded: Mutex<HashMap<String, Program>>,
+    /// How many times the configuration was read again.
+    pub reloads: AtomicU64,
+    /// Drives the HTTP connections.
+    pub runtime: Runtime,
+}
+
+impl Daemon {
+    pub fn new(
+        manager: Arc<Mutex<ProcessManager>>,
+        exit: Sender<()>,
+        config_file: Option<String>,
+    ) -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        Ok(Daemon {
+            manager,
+            exit,
+            config_file,
+            reloaded: Mutex::new(HashMap::new()),
+            reloads: AtomicU64::new(0),
+            runtime,
+        })
+    }
+}
+
+/// Removes the control socket a crashed taskmasterd left behind, which
+/// nobody accepts connections on any more. Returns whether there was one;
+/// fails if a taskmasterd still listens on it.
+pub fn remove_stale_socket(path: &str) -> io::Result<bool> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {}
+        // bind reports whatever is in the way
+        Ok(_) => return Ok(false),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    }
+    match UnixStream::connect(path) {
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "another taskmasterd is listening on it",
+        )),
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+            fs::remove_file(path)?;
+            Ok(true)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Creates the control socket with the configured owner and permissions.
+pub fn bind(config: &UnixHttpServer) -> io::Result<UnixListener> {
+    let path = config.file();
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, Permissions::from_mode(config.chmod()))?;
+    if let Some((user, group)) = config.chown() {
+        let user = User::from_name(user)?
+            .ok_or_else(|| io::Error::other(format!("{}: no such user", user)))?;
+        let gid = match group {
+            Some(group) => {
+                Group::from_name(group)?
+                    .ok_or_else(|| io::Error::other(format!("{}: no such group", group)))?
+                    .gid
+            }
+            None => user.gid,
+        };
+        nix::unistd::chown(path.as_str(), Some(user.uid), Some(gid))?;
+    }
+    Ok(listener)
+}
+
+/// Opens the control port. Anyone who can reach it could control every
+/// process, so only loopback addresses may go without credentials.
+pub fn bind_inet(config: &InetHttpServer) -> io::Result<TcpListener> {
+    let Some((host, port)) = config.port() else {
+        return Err(io::Error::other("no port configured"));
+    };
+    let addresses = (host.as_str(), *port)
+        .to_socket_addrs()?
+        .collect::<Vec<_>>();
+    let exposed = addresses.iter().any(|address| !address.ip().is_loopback());
+    if exposed && Credentials::from_config(config).is_none() {
+        return Err(io::Error::other(
+            "a username and password are required beyond loopback addresses",
+        ));
+    }
+    TcpListener::bind(addresses.as_slice())
+}
+
+/// Accepts clients of the control socket on their own threads.
+pub fn serve(listener: UnixListener, daemon: Arc<Daemon>) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let incoming = listener.incoming().map(|s| s.map(Connection::Unix));
+        accept(incoming, &daemon, None);
+    })
+}
+
+/// Accepts clients of the control port, which must authenticate first
+/// when credentials are configured.
+pub fn serve_inet(
+    listener: TcpListener,
+    daemon: Arc<Daemon>,
+    credentials: Option<Credentials>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let incoming = listener.incoming().map(|s| s.map(Connection::Tcp));
+        accept(incoming, &daemon, credentials);
+    })
+}
+
+/// HTTP requests, such as the XML-RPC calls of supervisorctl, go to the HTTP
+/// router and the rest speak JSON lines. Each connection is told apart on its
+/// own thread, so that a client which says nothing holds up nobody else.
+fn accept(
+    incoming: impl Iterator<Item = io::Result<Connection>>,
+    daemon: &Arc<Daemon>,
+    credentials: Option<Credentials>,
+) {
+    let router = http::router(daemon, credentials.clone());
+    for stream in incoming {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                let manager = daemon.manager.lock().unwrap();
+                manager.logger().error(&format!("control socket: {}", e));
+                continue;
+            }
+        };
+        let daemon = Arc::clone(daemon);
+        let credentials = credentials.clone();
+        let router = router.clone();
+        thread::spawn(move || {
+            let result = match is_json(&stream) {
+                Ok(true) => handle(stream, &daemon, credentials.as_ref()),
+                Ok(false) => http::serve(&daemon, stream, router),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                let manager = daemon.manager.lock().unwrap();
+                manager.logger().debug(&format!("control client: {}", e));
+            }
+        });
+    }
+}
+
+/// JSON requests are objects; a client that hung up at once has nothing to say.
+fn is_json(stream: &Connection) -> io::Result<bool> {
+    stream.set_read_timeout(Some(FIRST_BYTE_TIMEOUT))?;
+    let first = stream.peek()?;
+    stream.set_read_timeout(None)?;
+    Ok(matches!(first, None | Some(b'{')))
+}
+
+/// Reads the `Auth` request a client of a protected listener has to start with.
+fn authenticate(
+    stream: &Connection,
+    reader: &mut BufReader<Connection>,
+    credentials: &Credentials,
+) -> io::Result<bool> {
+    let authenticated = match protocol::receive(reader)? {
+        Some(Request::Auth { username, password }) => credentials.verify(&username, &password),
+        _ => false,
+    };
+    let response = match authenticated {
+        true => Response::Done,
+        false => Response::Error(RemoteError::from(&ExecError::Unauthorized)),
+    };
+    protocol::send(stream, &response)?;
+    Ok(authenticated)
+}
+
+fn handle(
+    stream: Connection,
+    daemon: &Daemon,
+    credentials: Option<&Credentials>,
+) -> io::Result<()> {
+    let (manager, exit) = (&daemon.manager, &daemon.exit);
+    let mut reader = BufReader::new(stream.try_clone()?);
+    if let Some(credentials) = credentials
+        && !authenticate(&stream, &mut reader, credentials)?
+    {
+        let manager = manager.lock().unwrap();
+        manager
+            .logger()
+            .warn("control client failed to authenticate");
+        return Ok(());
+    }
+    while let Some(request) = protocol::receive::<Request>(&mut reader)? {
+        match request {
+            Request::Events => return stream_events(&stream, manager),
+            Request::Attach { group, name } => {
+                return attach(&stream, reader, manager, &group, &name);
+            }
+            Request::Shutdown => {
+                manager
+                    .lock()
+                    .unwrap()
+                    .logger()
+                    .info("shutdown requested, stopping all processes");
+                let killed = ProcessManager::shutdown(manager);
+                protocol::send(&stream, &Response::Killed(killed))?;
+                let _ = exit.send(());
+                return Ok(());
+            }
+            request => {
+                let response = respond(request, &mut manager.lock().unwrap())
+                    .unwrap_or_else(|e| Response::Error(RemoteError::from(&e)));
+                protocol::send(&stream, &response)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn respond(request: Request, manager: &mut ProcessManager) -> Result<Response, ExecError> {
+    let response = match request {
+        Request::Auth { .. } => Response::Done,
+        Request::Resolve { targets } => Response::Targets(manager.collect_targets(&targets)?),
+        Request::Info { targets } => {
+            let infos = targets
+                .iter()
+                .map(|(group, name)| {
+                    let process = manager
+                        .get_process(group, name)
+                        .ok_or_else(|| ExecError::ProcessNameNotFound(name.clone()))?;
+                    Ok(process.info(group))
+                })
+                .collect::<Result<_, ExecError>>()?;
+            Response::Infos(infos)
+        }
+        Request::Start { group, name } => {
+            manager.start(&group, &name)?;
+            Response::Done
+        }
+        Request::Stop { group, name } => {
+            manager.stop(&group, &name)?;
+            Response::Done
+        }
+        Request::Signal {
+            group,
+            name,
+            signum,
+        } => {
+            let process = manager
+                .get_process(&group, &name)
+                .ok_or_else(|| ExecError::ProcessNameNotFound(name.clone()))?;
+            process.signal(signum, manager.logger())?;
+            Response::Done
+        }
+        Request::Clear { group, name } => {
+            let program = manager
+                .get_program(&group)
+                .ok_or_else(|| ExecError::ProcessNameNotFound(group.clone()))?;
+            let process = manager
+                .get_process(&group, &name)
+                .ok_or_else(|| ExecError::ProcessNameNotFound(name.clone()))?;
+            process.clear_logs(program)?;
+            Response::Done
+        }
+        Request::Avail => {
+            let avail = manager
+                .resolve_targets("all")?
+                .into_iter()
+                .map(|(group, name)| {
+                    let autostart = manager.get_program(&group).unwrap().autostart();
+                    AvailInfo {
+                        group,
+                        name,
+                        autostart,
+                    }
+                })
+                .collect();
+            Response::Avail(avail)
+        }
+        Request::Pid => Response::Pid(std::process::id()),
+        Request::CompletionNames => Response::Names(manager.completion_names()),
+        Request::Input { .. } => return Err(ExecError::InvalidArgs),
+        Request::Events | Request::Attach { .. } | Request::Shutdown => {
+            unreachable!("handled by the connection")
+        }
+    };
+    Ok(response)
+}
+
+fn stream_events(stream: &Connection, manager: &Arc<Mutex<ProcessManager>>) -> io::Result<()> {
+    let events = manager.lock().unwrap().subscribe();
+    protocol::send(stream, &Response::Done)?;
+    for event in events {
+        // a client that went away shows up as a failed write
+        protocol::send(stream, &Response::Event(event))?;
+    }
+    Ok(())
+}
+
+fn attach(
+    stream: &Connection,
+    mut reader: BufReader<Connection>,
+    manager: &Arc<Mutex<ProcessManager>>,
+    group: &str,
+    name: &str,
+) -> io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let stdin = {
+        let manager = manager.lock().unwrap();
+        let process = match manager.get_process(group, name) {
+            Some(process) if process.state().is_running() => process,
+            Some(_) => {
+                let e = ExecError::NotRunning(name.to_string());
+                return protocol::send(stream, &Response::Error(RemoteError::from(&e)));
+            }
+            None => {
+                let e = ExecError::ProcessNameNotFound(name.to_string());
+                return protocol::send(stream, &Response::Error(RemoteError::from(&e)));
+            }
+        };
+        process.stdout().lock().unwrap().subscribe(tx.clone());
+        process.stderr().lock().unwrap().subscribe(tx);
+        process.stdin()
+    };
+    protocol::send(stream, &Response::Done)?;
+    thread::spawn(move || {
+        while let Ok(Some(Request::Input { data })) = protocol::receive(&mut reader) {
+            if let Some(stdin) = &stdin
+                && stdin.lock().unwrap().write_all(&data).is_err()
+            {
+                break;
+            }
+        }
+    });
+    // the channel closes once the process has exited, which ends the session
+    for (stream_kind, data) in rx {
+        protocol::send(
+            stream,
+            &Response::Output {
+                stream: stream_kind,
+                data,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    mod remove_stale_socket_tests {
+        use super::*;
+
+        fn socket_path(name: &str) -> String {
+            let path = std::env::temp_dir().join(format!("{}-{}.sock", name, std::process::id()));
+            let _ = fs::remove_file(&path);
+            path.to_string_lossy().into_owned()
+        }
+
+        #[test]
+        fn test_remove_stale_socket() {
+            let path = socket_path("taskmaster-stale");
+            drop(UnixListener::bind(&path).unwrap());
+            assert!(remove_stale_socket(&path).unwrap());
+            assert!(!Path::new(&path).exists());
+            assert!(!remove_stale_socket(&path).unwrap());
+        }
+
+        #[test]
+        fn test_remove_stale_socket_in_use() {
+            let path = socket_path("taskmaster-live");
+            let listener = UnixListener::bind(&path).unwrap();
+            let error = remove_stale_socket(&path).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+            assert!(Path::new(&path).exists());
+            drop(listener);
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
@@ -0,0 +1,763 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dxr::{Fault, TryFromValue, TryToValue, Value};
+use dxr_server::axum::Router;
+use dxr_server::axum::http::HeaderMap;
+use dxr_server::{Handler, HandlerResult, RouteBuilder, async_trait};
+
+use crate::config::adapter::Adapter;
+use crate::config::program::Program;
+use crate::config::runtimecontext::RuntimeContext;
+use crate::control::server::Daemon;
+use crate::errors::ExecError;
+use crate::exec::process::{Process, ProcessState};
+use crate::exec::process_manager::TICK;
+use crate::exec::signal;
+
+/// Where supervisorctl sends its calls.
+pub const PATH: &str = "/RPC2";
+
+/// The interface version supervisorctl checks before any other call.
+const API_VERSION: &str = "3.0";
+
+/// The fault codes of supervisord's `supervisor` namespace.
+pub mod faults {
+    pub const UNKNOWN_METHOD: i32 = 1;
+    pub const INCORRECT_PARAMETERS: i32 = 2;
+    pub const BAD_ARGUMENTS: i32 = 3;
+    pub const BAD_NAME: i32 = 10;
+    pub const BAD_SIGNAL: i32 = 11;
+    pub const NO_FILE: i32 = 20;
+    pub const FAILED: i32 = 30;
+    pub const ABNORMAL_TERMINATION: i32 = 40;
+    pub const SPAWN_ERROR: i32 = 50;
+    pub const ALREADY_STARTED: i32 = 60;
+    pub const NOT_RUNNING: i32 = 70;
+    pub const SUCCESS: i32 = 80;
+    pub const ALREADY_ADDED: i32 = 90;
+    pub const STILL_RUNNING: i32 = 91;
+    pub const CANT_REREAD: i32 = 92;
+
+    pub fn name(code: i32) -> &'static str {
+        match code {
+            UNKNOWN_METHOD => "UNKNOWN_METHOD",
+            INCORRECT_PARAMETERS => "INCORRECT_PARAMETERS",
+            BAD_ARGUMENTS => "BAD_ARGUMENTS",
+            BAD_NAME => "BAD_NAME",
+            BAD_SIGNAL => "BAD_SIGNAL",
+            NO_FILE => "NO_FILE",
+            ABNORMAL_TERMINATION => "ABNORMAL_TERMINATION",
+            SPAWN_ERROR => "SPAWN_ERROR",
+            ALREADY_STARTED => "ALREADY_STARTED",
+            NOT_RUNNING => "NOT_RUNNING",
+            SUCCESS => "SUCCESS",
+            ALREADY_ADDED => "ALREADY_ADDED",
+            STILL_RUNNING => "STILL_RUNNING",
+            CANT_REREAD => "CANT_REREAD",
+            _ => "FAILED",
+        }
+    }
+}
+
+type Method = fn(&Daemon, &[Value]) -> HandlerResult;
+
+type LogFile = fn(&Program) -> &String;
+
+const METHODS: [(&str, Method); 25] = [
+    ("supervisor.getAPIVersion", get_api_version),
+    ("supervisor.getVersion", get_api_version),
+    ("supervisor.getSupervisorVersion", get_supervisor_version),
+    ("supervisor.getIdentification", get_identification),
+    ("supervisor.getPID", get_pid),
+    ("supervisor.getState", get_state),
+    ("supervisor.getAllProcessInfo", get_all_process_info),
+    ("supervisor.getProcessInfo", get_process_info),
+    ("supervisor.startProcess", start_process),
+    ("supervisor.stopProcess", stop_process),
+    ("supervisor.startProcessGroup", start_process_group),
+    ("supervisor.stopProcessGroup", stop_process_group),
+    ("supervisor.startAllProcesses", start_all_processes),
+    ("supervisor.stopAllProcesses", stop_all_processes),
+    ("supervisor.signalProcess", signal_process),
+    ("supervisor.readProcessStdoutLog", read_process_stdout_log),
+    ("supervisor.readProcessStderrLog", read_process_stderr_log),
+    ("supervisor.tailProcessStdoutLog", tail_process_stdout_log),
+    ("supervisor.tailProcessStderrLog", tail_process_stderr_log),
+    ("supervisor.clearProcessLogs", clear_process_logs),
+    ("supervisor.reloadConfig", reload_config),
+    ("supervisor.addProcessGroup", add_process_group),
+    ("supervisor.removeProcessGroup", remove_process_group),
+    ("supervisor.shutdown", shutdown),
+    ("supervisor.restart", restart),
+];
+
+/// A method bound to the daemon it acts on.
+struct Call {
+    daemon: Arc<Daemon>,
+    method: Method,
+}
+
+#[async_trait]
+impl Handler for Call {
+    async fn handle(&self, params: &[Value], _headers: HeaderMap) -> HandlerResult {
+        let daemon = Arc::clone(&self.daemon);
+        let method = self.method;
+        let params = params.to_vec();
+        // methods lock the ProcessManager and may sleep while waiting on a process
+        tokio::task::spawn_blocking(move || method(&daemon, &params))
+            .await
+            .unwrap_or_else(|e| Err(fault(faults::FAILED, e)))
+    }
+}
+
+pub fn route(daemon: &Arc<Daemon>) -> Router {
+    METHODS
+        .iter()
+        .fold(
+            RouteBuilder::new().set_path(PATH),
+            |route, (name, method)| {
+                let call = Call {
+                    daemon: Arc::clone(daemon),
+                    method: *method,
+                };
+                route.add_method(name, Box::new(call))
+            },
+        )
+        .build()
+}
+
+/// The process info struct of supervisord.
+#[derive(Debug, TryToValue)]
+struct ProcessStatus {
+    name: String,
+    group: String,
+    description: String,
+    start: i32,
+    stop: i32,
+    now: i32,
+    state: i32,
+    statename: String,
+    spawnerr: String,
+    exitstatus: i32,
+    logfile: String,
+    stdout_logfile: String,
+    stderr_logfile: String,
+    pid: i32,
+}
+
+/// The outcome for one process of a group or `all` call.
+#[derive(Debug, TryToValue)]
+struct ProcessResult {
+    name: String,
+    group: String,
+    status: i32,
+    description: String,
+}
+
+#[derive(Debug, TryToValue)]
+struct DaemonState {
+    statecode: i32,
+    statename: String,
+}
+
+fn fault(code: i32, detail: impl std::fmt::Display) -> Fault {
+    Fault::new(code, format!("{}: {}", faults::name(code), detail))
+}
+
+fn exec_fault(e: &ExecError) -> Fault {
+    match e {
+        ExecError::ProcessNameNotFound(name) | ExecError::InvalidPattern(name) => {
+            fault(faults::BAD_NAME, name)
+        }
+        ExecError::InvalidSignal(name) => fault(faults::BAD_SIGNAL, name),
+        ExecError::AlreadyStarted(name) => fault(faults::ALREADY_STARTED, name),
+        ExecError::NotRunning(name) => fault(faults::NOT_RUNNING, name),
+        ExecError::StillRunning(name) => fault(faults::STILL_RUNNING, name),
+        ExecError::AlreadyAdded(name) => fault(faults::ALREADY_ADDED, name),
+        ExecError::SpawnFailed(name) => fault(faults::SPAWN_ERROR, name),
+        ExecError::UnexpectedState(name, ProcessState::FATAL) => fault(faults::SPAWN_ERROR, name),
+        ExecError::UnexpectedState(name, _) => fault(faults::ABNORMAL_TERMINATION, name),
+        e => fault(faults::FAILED, e),
+    }
+}
+
+fn value(value: impl TryToValue) -> HandlerResult {
+    value.try_to_value().map_err(|e| fault(faults::FAILED, e))
+}
+
+/// Takes the parameter at `index`, or `default` when the caller left it out.
+fn param<T: TryFromValue>(params: &[Value], index: usize, default: Option<T>) -> Result<T, Fault> {
+    match params.get(index) {
+        Some(value) => T::try_from_value(value).map_err(|e| fault(faults::INCORRECT_PARAMETERS, e)),
+        None => default.ok_or_else(|| fault(faults::INCORRECT_PARAMETERS, "missing parameter")),
+    }
+}
+
+fn arity(params: &[Value], max: usize) -> Result<(), Fault> {
+    match params.len() > max {
+        true => Err(fault(faults::INCORRECT_PARAMETERS, "too many parameters")),
+        false => Ok(()),
+    }
+}
+
+fn get_api_version(_: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    value(API_VERSION)
+}
+
+fn get_supervisor_version(_: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    value(env!("CARGO_PKG_VERSION"))
+}
+
+fn get_identification(_: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    value("taskmaster")
+}
+
+fn get_pid(_: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    value(std::process::id() as i32)
+}
+
+fn get_state(_: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    value(DaemonState {
+        statecode: 1,
+        statename: "RUNNING".to_string(),
+    })
+}
+
+fn status(daemon: &Daemon, targets: &[(String, String)]) -> Vec<ProcessStatus> {
+    let manager = daemon.manager.lock().unwrap();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs()) as i32;
+    targets
+        .iter()
+        .filter_map(|(group, name)| {
+            let program = manager.get_program(group)?;
+            let info = manager.get_process(group, name)?.info(group);
+            let spawnerr = match info.state {
+                ProcessState::BACKOFF | ProcessState::FATAL => info.description.clone(),
+                _ => String::new(),
+            };
+            Some(ProcessStatus {
+                start: match info.state.is_running() {
+                    true => now - info.uptime as i32,
+                    false => 0,
+                },
+                stop: 0,
+                now,
+                state: info.state.code(),
+                statename: info.state.to_string(),
+                spawnerr,
+                exitstatus: info.exitstatus.unwrap_or(0),
+                logfile: program.stdout_logfile().clone(),
+                stdout_logfile: program.stdout_logfile().clone(),
+                stderr_logfile: program.stderr_logfile().clone(),
+                pid: info.pid as i32,
+                name: info.name,
+                group: info.group,
+                description: info.description,
+            })
+        })
+        .collect()
+}
+
+fn get_all_process_info(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    let targets = daemon
+        .manager
+        .lock()
+        .unwrap()
+        .resolve_targets("all")
+        .unwrap_or_default();
+    value(status(daemon, &targets))
+}
+
+/// Resolves the `name` parameter, which may also be `group:*`.
+fn targets(daemon: &Daemon, name: &str) -> Result<Vec<(String, String)>, Fault> {
+    let manager = daemon.manager.lock().unwrap();
+    manager.resolve_targets(name).map_err(|e| exec_fault(&e))
+}
+
+fn get_process_info(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 1)?;
+    let name: String = param(params, 0, None)?;
+    match targets(daemon, &name)?.as_slice() {
+        [target] => value(status(daemon, std::slice::from_ref(target)).remove(0)),
+        _ => Err(fault(faults::BAD_NAME, name)),
+    }
+}
+
+/// Starts or stops every target, then optionally waits for each of them to
+/// settle, reporting one result per process.
+fn apply(
+    daemon: &Daemon,
+    targets: Vec<(String, String)>,
+    start: bool,
+    wait: bool,
+) -> Vec<(String, String, Result<(), Fault>)> {
+    let mut results = Vec::new();
+    let mut pending = Vec::new();
+    {
+        let mut manager = daemon.manager.lock().unwrap();
+        for (group, name) in targets {
+            let result = match start {
+                true => manager.start(&group, &name),
+                false => manager.stop(&group, &name),
+            };
+            match result {
+                Ok(()) if wait => pending.push((group, name)),
+                result => results.push((group, name, result.map_err(|e| exec_fault(&e)))),
+            }
+        }
+    }
+    while !pending.is_empty() {
+        thread::sleep(TICK);
+        let manager = daemon.manager.lock().unwrap();
+        pending.retain(|(group, name)| {
+            // another client may have removed its group in the meantime
+            let state = manager.get_process(group, name).map(Process::state);
+            let result = match (start, state) {
+                (false, None) => Ok(()),
+                (true, None) => Err(fault(faults::BAD_NAME, name)),
+                (true, Some(ProcessState::RUNNING)) | (false, Some(ProcessState::STOPPED)) => {
+                    Ok(())
+                }
+                (true, Some(ProcessState::STARTING | ProcessState::BACKOFF))
+                | (false, Some(ProcessState::STOPPING)) => return true,
+                (_, Some(state)) => {
+                    Err(exec_fault(&ExecError::UnexpectedState(name.clone(), state)))
+                }
+            };
+            results.push((group.clone(), name.clone(), result));
+            false
+        });
+    }
+    results.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    results
+}
+
+/// The single process call form: true, or the first fault.
+fn apply_one(daemon: &Daemon, params: &[Value], start: bool) -> HandlerResult {
+    arity(params, 2)?;
+    let name: String = param(params, 0, None)?;
+    let wait: bool = param(params, 1, Some(true))?;
+    let targets = targets(daemon, &name)?;
+    for (_, _, result) in apply(daemon, targets, start, wait) {
+        result?;
+    }
+    value(true)
+}
+
+/// The group and `all` call form: one result struct per process.
+fn apply_many(
+    daemon: &Daemon,
+    targets: Vec<(String, String)>,
+    start: bool,
+    wait: bool,
+) -> HandlerResult {
+    let results: Vec<ProcessResult> = apply(daemon, targets, start, wait)
+        .into_iter()
+        .map(|(group, name, result)| {
+            let (status, description) = match result {
+                Ok(()) => (faults::SUCCESS, "OK".to_string()),
+                Err(fault) => (fault.code(), fault.string().to_string()),
+            };
+            ProcessResult {
+                name,
+                group,
+                status,
+                description,
+            }
+        })
+        .collect();
+    value(results)
+}
+
+fn group_targets(
+    daemon: &Daemon,
+    params: &[Value],
+) -> Result<(Vec<(String, String)>, bool), Fault> {
+    arity(params, 2)?;
+    let name: String = param(params, 0, None)?;
+    let wait: bool = param(params, 1, Some(true))?;
+    let manager = daemon.manager.lock().unwrap();
+    if manager.get_program(&name).is_none() {
+        return Err(fault(faults::BAD_NAME, name));
+    }
+    let targets = manager
+        .resolve_targets(&format!("{}:*", name))
+        .map_err(|e| exec_fault(&e))?;
+    Ok((targets, wait))
+}
+
+fn all_targets(daemon: &Daemon, params: &[Value]) -> Result<(Vec<(String, String)>, bool), Fault> {
+    arity(params, 1)?;
+    let wait: bool = param(params, 0, Some(true))?;
+    let manager = daemon.manager.lock().unwrap();
+    Ok((manager.resolve_targets("all").unwrap_or_default(), wait))
+}
+
+fn start_process(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    apply_one(daemon, params, true)
+}
+
+fn stop_process(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    apply_one(daemon, params, false)
+}
+
+fn start_process_group(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    let (targets, wait) = group_targets(daemon, params)?;
+    apply_many(daemon, targets, true, wait)
+}
+
+fn stop_process_group(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    let (targets, wait) = group_targets(daemon, params)?;
+    apply_many(daemon, targets, false, wait)
+}
+
+fn start_all_processes(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    let (targets, wait) = all_targets(daemon, params)?;
+    // only what is down; supervisord does not report running processes as failures
+    let targets = targets
+        .into_iter()
+        .filter(|(group, name)| {
+            let manager = daemon.manager.lock().unwrap();
+            manager
+                .get_process(group, name)
+                .is_some_and(|process| !process.state().is_running())
+        })
+        .collect();
+    apply_many(daemon, targets, true, wait)
+}
+
+fn stop_all_processes(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    let (targets, wait) = all_targets(daemon, params)?;
+    let targets = targets
+        .into_iter()
+        .filter(|(group, name)| {
+            let manager = daemon.manager.lock().unwrap();
+            manager
+                .get_process(group, name)
+                .is_some_and(|process| process.state().is_running())
+        })
+        .collect();
+    apply_many(daemon, targets, false, wait)
+}
+
+fn signal_process(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 2)?;
+    let name: String = param(params, 0, None)?;
+    // supervisorctl sends the signal as a string, other clients as a number
+    let signame = match param::<String>(params, 1, None) {
+        Ok(signame) => signame,
+        Err(_) => param::<i32>(params, 1, None)?.to_string(),
+    };
+    let signum = signal::from_str(&signame).ok_or_else(|| fault(faults::BAD_SIGNAL, &signame))?;
+    let targets = targets(daemon, &name)?;
+    let manager = daemon.manager.lock().unwrap();
+    for (group, name) in targets {
+        let process = manager
+            .get_process(&group, &name)
+            .ok_or_else(|| fault(faults::BAD_NAME, &name))?;
+        process
+            .signal(signum, manager.logger())
+            .map_err(|e| exec_fault(&e))?;
+    }
+    value(true)
+}
+
+/// Which bytes of a `size` byte log to return, following supervisord: a
+/// negative offset counts from the end and a zero length reads to the end.
+fn log_range(size: u64, offset: i64, length: i64) -> Option<(u64, u64)> {
+    if offset < 0 {
+        if length != 0 {
+            return None;
+        }
+        let start = size.saturating_sub(offset.unsigned_abs());
+        return Some((start, size - start));
+    }
+    let offset = offset as u64;
+    if length < 0 || offset > size {
+        return None;
+    }
+    let available = size - offset;
+    match length {
+        0 => Some((offset, available)),
+        length => Some((offset, available.min(length as u64))),
+    }
+}
+
+/// The log file of the single process `name`.
+fn log_path(daemon: &Daemon, name: &str, logfile: LogFile) -> Result<String, Fault> {
+    match targets(daemon, name)?.as_slice() {
+        [(group, _)] => {
+            let manager = daemon.manager.lock().unwrap();
+            let program = manager
+                .get_program(group)
+                .ok_or_else(|| fault(faults::BAD_NAME, name))?;
+            Ok(logfile(program).clone())
+        }
+        _ => Err(fault(faults::BAD_NAME, name)),
+    }
+}
+
+fn read_bytes(file: &mut File, start: u64, length: u64) -> Result<String, Fault> {
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(start))
+        .and_then(|_| file.take(length).read_to_end(&mut data))
+        .map_err(|e| fault(faults::FAILED, e))?;
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
+
+fn read_log(daemon: &Daemon, params: &[Value], logfile: LogFile) -> HandlerResult {
+    arity(params, 3)?;
+    let name: String = param(params, 0, None)?;
+    let offset: i32 = param(params, 1, None)?;
+    let length: i32 = param(params, 2, None)?;
+    let path = log_path(daemon, &name, logfile)?;
+    let mut file = File::open(&path).map_err(|_| fault(faults::NO_FILE, &path))?;
+    let size = file
+        .metadata()
+        .map_err(|_| fault(faults::NO_FILE, &path))?
+        .len();
+    let (start, length) = log_range(size, offset as i64, length as i64)
+        .ok_or_else(|| fault(faults::BAD_ARGUMENTS, "offset or length out of range"))?;
+    value(read_bytes(&mut file, start, length)?)
+}
+
+fn read_process_stdout_log(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    read_log(daemon, params, Program::stdout_logfile)
+}
+
+fn read_process_stderr_log(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    read_log(daemon, params, Program::stderr_logfile)
+}
+
+/// Which bytes `tail -f` gets next, following supervisord: at most `length`
+/// bytes from `offset`, or the last `length` bytes with the overflow flag set
+/// when more than that was written since.
+fn tail_range(size: u64, offset: u64, length: u64) -> (u64, u64, bool) {
+    if size > offset + length {
+        return (size - length, length, true);
+    }
+    let start = offset.min(size);
+    (start, size - start, false)
+}
+
+/// Returns `[data, next offset, overflow]`; a log that does not exist yet is empty.
+fn tail_log(daemon: &Daemon, params: &[Value], logfile: LogFile) -> HandlerResult {
+    arity(params, 3)?;
+    let name: String = param(params, 0, None)?;
+    let offset: i32 = param(params, 1, None)?;
+    let length: i32 = param(params, 2, None)?;
+    if offset < 0 || length < 0 {
+        return Err(fault(faults::BAD_ARGUMENTS, "negative offset or length"));
+    }
+    let path = log_path(daemon, &name, logfile)?;
+    let Ok(mut file) = File::open(&path) else {
+        return value((String::new(), 0, false));
+    };
+    let size = file.metadata().map_err(|e| fault(faults::FAILED, e))?.len();
+    let (start, length, overflow) = tail_range(size, offset as u64, length as u64);
+    let data = read_bytes(&mut file, start, length)?;
+    value((data, size as i32, overflow))
+}
+
+fn tail_process_stdout_log(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    tail_log(daemon, params, Program::stdout_logfile)
+}
+
+fn tail_process_stderr_log(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    tail_log(daemon, params, Program::stderr_logfile)
+}
+
+fn clear_process_logs(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 1)?;
+    let name: String = param(params, 0, None)?;
+    let targets = targets(daemon, &name)?;
+    let manager = daemon.manager.lock().unwrap();
+    for (group, name) in targets {
+        let (Some(program), Some(process)) = (
+            manager.get_program(&group),
+            manager.get_process(&group, &name),
+        ) else {
+            return Err(fault(faults::BAD_NAME, &name));
+        };
+        process.clear_logs(program).map_err(|e| exec_fault(&e))?;
+    }
+    value(true)
+}
+
+/// Compares the running programs with freshly read ones: added, changed, removed.
+fn diff(current: &[&Program], reloaded: &HashMap<String, Program>) -> [Vec<String>; 3] {
+    let mut added: Vec<String> = reloaded
+        .keys()
+        .filter(|name| current.iter().all(|p| p.programname() != *name))
+        .cloned()
+        .collect();
+    let mut changed = Vec::new();
+    let mut removed = Vec::new();
+    for program in current {
+        match reloaded.get(program.programname()) {
+            Some(new) if new != *program => changed.push(program.programname().clone()),
+            Some(_) => {}
+            None => removed.push(program.programname().clone()),
+        }
+    }
+    added.sort();
+    changed.sort();
+    removed.sort();
+    [added, changed, removed]
+}
+
+/// Reads the configuration again and reports what differs; `addProcessGroup`
+/// and `removeProcessGroup` then apply the changes.
+fn reload_config(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    let mut runtime_context = RuntimeContext::default();
+    Adapter::parse_config(&mut runtime_context, daemon.config_file.as_ref())
+        .map_err(|e| fault(faults::CANT_REREAD, e))?;
+    let reloaded = runtime_context.config().programs().clone();
+    let changes = {
+        let manager = daemon.manager.lock().unwrap();
+        manager.logger().info("configuration reloaded");
+        let current: Vec<&Program> = manager.programs().collect();
+        diff(&current, &reloaded)
+    };
+    *daemon.reloaded.lock().unwrap() = reloaded;
+    daemon.reloads.fetch_add(1, Ordering::Relaxed);
+    value(vec![changes.to_vec()])
+}
+
+fn add_process_group(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 1)?;
+    let name: String = param(params, 0, None)?;
+    let reloaded = daemon.reloaded.lock().unwrap();
+    let program = reloaded
+        .get(&name)
+        .ok_or_else(|| fault(faults::BAD_NAME, &name))?;
+    let mut manager = daemon.manager.lock().unwrap();
+    manager.add_group(program).map_err(|e| exec_fault(&e))?;
+    manager
+        .logger()
+        .info(&format!("added process group {}", name));
+    value(true)
+}
+
+fn remove_process_group(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 1)?;
+    let name: String = param(params, 0, None)?;
+    let mut manager = daemon.manager.lock().unwrap();
+    manager.remove_group(&name).map_err(|e| exec_fault(&e))?;
+    manager
+        .logger()
+        .info(&format!("removed process group {}", name));
+    value(true)
+}
+
+fn shutdown(daemon: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    daemon
+        .manager
+        .lock()
+        .unwrap()
+        .logger()
+        .info("shutdown requested over XML-RPC, stopping all processes");
+    let exit = daemon.exit.clone();
+    thread::spawn(move || {
+        // give the response a head start on the orderly shutdown
+        thread::sleep(TICK);
+        let _ = exit.send(());
+    });
+    value(true)
+}
+
+/// Restarting in place is not supported; taskmasterd only shuts down.
+fn restart(_: &Daemon, params: &[Value]) -> HandlerResult {
+    arity(params, 0)?;
+    Err(fault(
+        faults::FAILED,
+        "restart is not supported, use shutdown",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod log_range_tests {
+        use super::*;
+
+        #[test]
+        fn test_log_range_from_offset() {
+            assert_eq!(log_range(100, 10, 20), Some((10, 20)));
+            assert_eq!(log_range(100, 90, 20), Some((90, 10)));
+            assert_eq!(log_range(100, 10, 0), Some((10, 90)));
+        }
+
+        #[test]
+        fn test_log_range_from_end() {
+            assert_eq!(log_range(100, -30, 0), Some((70, 30)));
+            assert_eq!(log_range(10, -30, 0), Some((0, 10)));
+        }
+
+        #[test]
+        fn test_log_range_invalid() {
+            assert_eq!(log_range(100, -30, 5), None);
+            assert_eq!(log_range(100, 10, -1), None);
+            assert_eq!(log_range(100, 101, 0), None);
+        }
+    }
+
+    mod tail_range_tests {
+        use super::*;
+
+        #[test]
+        fn test_tail_range_follows() {
+            assert_eq!(tail_range(100, 90, 1600), (90, 10, false));
+            assert_eq!(tail_range(100, 100, 1600), (100, 0, false));
+        }
+
+        #[test]
+        fn test_tail_range_overflow() {
+            assert_eq!(tail_range(5000, 0, 1600), (3400, 1600, true));
+        }
+
+        #[test]
+        fn test_tail_range_truncated() {
+            assert_eq!(tail_range(10, 100, 1600), (10, 0, false));
+        }
+    }
+
+    mod fault_tests {
+        use super::*;
+
+        #[test]
+        fn test_exec_fault_codes() {
+            let f = exec_fault(&ExecError::ProcessNameNotFound("web".to_string()));
+            assert_eq!((f.code(), f.string()), (faults::BAD_NAME, "BAD_NAME: web"));
+            let f = exec_fault(&ExecError::AlreadyStarted("web".to_string()));
+            assert_eq!(f.code(), faults::ALREADY_STARTED);
+            let f = exec_fault(&ExecError::UnexpectedState(
+                "web".to_string(),
+                ProcessState::FATAL,
+            ));
+            assert_eq!(f.code(), faults::SPAWN_ERROR);
+            let f = exec_fault(&ExecError::UnexpectedState(
+                "web".to_string(),
+                ProcessState::EXITED,
+            ));
+            assert_eq!(f.code(), faults::ABNORMAL_TERMINATION);
+        }
+    }
+}
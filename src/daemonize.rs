@@ -0,0 +1,228 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::fd::AsRawFd;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Detaches from the terminal the classic way: forks, starts a new session
+/// and forks again so that the daemon can never reacquire a terminal, then
+/// sends stdout and stderr to `logfile` (or nowhere when it is empty).
+///
+/// Only the calling thread survives a fork, so this must run before any
+/// other thread is started.
+pub fn daemonize(logfile: &str) -> io::Result<()> {
+    // opened first so that a bad path is still reported on the terminal
+    let output = match logfile.is_empty() {
+        true => OpenOptions::new().write(true).open("/dev/null")?,
+        false => OpenOptions::new().create(true).append(true).open(logfile)?,
+    };
+    let null = File::open("/dev/null")?;
+    fork_and_exit_parent()?;
+    if unsafe { libc::setsid() } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    fork_and_exit_parent()?;
+    io::stdout().flush()?;
+    for (file, fd) in [
+        (&null, libc::STDIN_FILENO),
+        (&output, libc::STDOUT_FILENO),
+        (&output, libc::STDERR_FILENO),
+    ] {
+        if unsafe { libc::dup2(file.as_raw_fd(), fd) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn fork_and_exit_parent() -> io::Result<()> {
+    match unsafe { libc::fork() } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => Ok(()),
+        // the child carries on, skip the parent's destructors
+        _ => unsafe { libc::_exit(0) },
+    }
+}
+
+/// The file holding the pid of the running taskmasterd, for init scripts.
+/// It stays locked while taskmasterd runs, so that a second one started for
+/// the same configuration refuses to fight over the children.
+#[derive(Debug)]
+pub struct Pidfile {
+    path: String,
+    file: File,
+    /// The pid a taskmasterd that did not exit cleanly left behind.
+    stale_pid: Option<u32>,
+}
+
+impl Pidfile {
+    /// Opens and locks the pidfile, failing if another taskmasterd holds it.
+    pub fn lock(path: &str) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let error = match unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } {
+            0 => None,
+            _ => Some(io::Error::last_os_error()),
+        };
+        // a pid in an unlocked file is that of a daemon which crashed
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let pid = contents.trim().parse::<u32>().ok();
+        if let Some(error) = error {
+            if error.kind() != io::ErrorKind::WouldBlock {
+                return Err(error);
+            }
+            let message = match pid {
+                Some(pid) => format!("taskmasterd is already running with pid {}", pid),
+                None => "taskmasterd is already running".to_string(),
+            };
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, message));
+        }
+        Ok(Pidfile {
+            path: path.to_string(),
+            file,
+            stale_pid: pid,
+        })
+    }
+
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    pub fn stale_pid(&self) -> Option<u32> {
+        self.stale_pid
+    }
+
+    /// Replaces whatever the file held by the pid of this process, which
+    /// changes when it daemonizes.
+    pub fn write_pid(&mut self) -> io::Result<()> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        writeln!(self.file, "{}", std::process::id())
+    }
+
+    /// Removes the file; the lock goes with it.
+    pub fn remove(self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The processes a taskmasterd started, listed next to its pidfile so that
+/// the next one can stop those a crash left behind. Each of them leads its
+/// own process group, so their pids are also the groups to signal.
+#[derive(Debug, Clone)]
+pub struct Children {
+    path: String,
+}
+
+impl Children {
+    pub fn new(pidfile: &str) -> Self {
+        Children {
+            path: format!("{}.children", pidfile),
+        }
+    }
+
+    pub fn write(&self, children: &[(String, u32)]) -> io::Result<()> {
+        let contents: String = children
+            .iter()
+            .map(|(name, pid)| format!("{} {}\n", pid, name))
+            .collect();
+        fs::write(&self.path, contents)
+    }
+
+    /// Those listed whose process group is still around.
+    pub fn alive(&self) -> Vec<(String, u32)> {
+        let contents = fs::read_to_string(&self.path).unwrap_or_default();
+        contents
+            .lines()
+            .filter_map(|line| {
+                let (pid, name) = line.split_once(' ')?;
+                Some((name.to_string(), pid.parse::<u32>().ok()?))
+            })
+            .filter(|(_, pid)| *pid > 1 && signal_group(*pid, 0))
+            .collect()
+    }
+
+    pub fn remove(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn signal_group(pgid: u32, signum: libc::c_int) -> bool {
+    unsafe { libc::kill(-(pgid as libc::pid_t), signum) == 0 }
+}
+
+/// Asks the process groups of `children` to terminate, and kills those
+/// still there after `timeout`. Returns the ones that had to be killed.
+pub fn stop_orphans(children: &[(String, u32)], timeout: Duration) -> Vec<(String, u32)> {
+    for (_, pid) in children {
+        signal_group(*pid, libc::SIGTERM);
+    }
+    let deadline = Instant::now() + timeout;
+    let mut remaining = children.to_vec();
+    while !remaining.is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+        remaining.retain(|(_, pid)| signal_group(*pid, 0));
+    }
+    for (_, pid) in &remaining {
+        signal_group(*pid, libc::SIGKILL);
+    }
+    remaining
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod pidfile_tests {
+        use super::*;
+
+        #[test]
+        fn test_lock_excludes_second_instance() {
+            let path = std::env::temp_dir().join(format!("taskmaster-{}.pid", std::process::id()));
+            let path = path.to_string_lossy().into_owned();
+            fs::write(&path, "4242\n").unwrap();
+            let mut pidfile = Pidfile::lock(&path).unwrap();
+            assert_eq!(pidfile.stale_pid(), Some(4242));
+            pidfile.write_pid().unwrap();
+            let error = Pidfile::lock(&path).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+            assert!(error.to_string().contains(&std::process::id().to_string()));
+            pidfile.remove();
+            assert!(!std::path::Path::new(&path).exists());
+        }
+    }
+
+    mod children_tests {
+        use super::*;
+        use std::os::unix::process::CommandExt;
+        use std::process::Command;
+
+        #[test]
+        fn test_stop_orphans() {
+            let pidfile =
+                std::env::temp_dir().join(format!("taskmaster-{}-orphans.pid", std::process::id()));
+            let children = Children::new(&pidfile.to_string_lossy());
+            let mut child = Command::new("sleep")
+                .arg("30")
+                .process_group(0)
+                .spawn()
+                .unwrap();
+            children
+                .write(&[
+                    ("sleep:sleep_0".to_string(), child.id()),
+                    ("gone:gone_0".to_string(), 999_999),
+                ])
+                .unwrap();
+            let alive = children.alive();
+            assert_eq!(alive, vec![("sleep:sleep_0".to_string(), child.id())]);
+            stop_orphans(&alive, Duration::from_millis(200));
+            assert!(child.wait().unwrap().code().is_none());
+            children.remove();
+        }
+    }
+}
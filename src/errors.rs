@@ -1,22 +1,104 @@
 use ini::Error as IniError;
 use thiserror::Error;
 
+use crate::control::protocol::RemoteError;
+use crate::exec::process::ProcessState;
+
 #[derive(Error, Debug)]
 pub enum CommandLineError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("line editor error: {0}")]
+    Readline(#[from] rustyline::error::ReadlineError),
     #[error("empty command.")]
     EmptyCommand,
+    #[error("unterminated quote: {0}")]
+    UnterminatedQuote(char),
+    #[error("trailing backslash.")]
+    TrailingBackslash,
+    #[error("end of input.")]
+    Eof,
 }
 
 #[derive(Error, Debug)]
 pub enum ExecError {
     #[error("invalid arguments.")]
     InvalidArgs,
-    #[error("invalid arguments length.")]
-    InvalidLength,
-    #[error("process name not found.")]
-    ProcessNameNotFound,
+    #[error("usage: {0}")]
+    Usage(String),
+    #[error("{0}: no such process.")]
+    ProcessNameNotFound(String),
+    #[error("{0}: names more than one process.")]
+    AmbiguousName(String),
+    #[error("{0}: already started.")]
+    AlreadyStarted(String),
+    #[error("{0}: not running.")]
+    NotRunning(String),
+    #[error("{0}: still running.")]
+    StillRunning(String),
+    #[error("{0}: already added.")]
+    AlreadyAdded(String),
+    #[error("{0}: spawn error.")]
+    SpawnFailed(String),
+    #[error("invalid pattern: {0}")]
+    InvalidPattern(String),
+    #[error("{0}: invalid signal.")]
+    InvalidSignal(String),
+    #[error("{0}: entered {1} state.")]
+    UnexpectedState(String, ProcessState),
+    #[error("{0}: timed out.")]
+    Timeout(String),
+    #[error("needs an interactive terminal.")]
+    NotATerminal,
+    #[error("cannot connect to taskmasterd at {0}: {1}")]
+    Unreachable(String, std::io::Error),
+    #[error("{}", .0.message)]
+    Remote(RemoteError),
+    #[error("authentication failed.")]
+    Unauthorized,
+    #[error("refused as a possible cross-site request: {0}")]
+    CrossSite(String),
+    #[error("*** Unknown syntax: {0}")]
+    UnknownCommand(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A failure that has already been printed next to its target.
+    #[error("")]
+    Reported(Box<ExecError>),
+}
+
+impl ExecError {
+    /// The status taskmasterctl exits with.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ExecError::ProcessNameNotFound(_) => 2,
+            ExecError::NotRunning(_) => 3,
+            ExecError::SpawnFailed(_) => 4,
+            ExecError::Timeout(_) => 5,
+            ExecError::Remote(e) => e.code,
+            ExecError::Reported(e) => e.exit_code(),
+            _ => 1,
+        }
+    }
+
+    /// The message without the process name, for per-target result lines.
+    pub fn reason(&self) -> String {
+        match self {
+            ExecError::ProcessNameNotFound(_) => "no such process".to_string(),
+            ExecError::AmbiguousName(_) => "ambiguous name".to_string(),
+            ExecError::AlreadyStarted(_) => "already started".to_string(),
+            ExecError::NotRunning(_) => "not running".to_string(),
+            ExecError::StillRunning(_) => "still running".to_string(),
+            ExecError::AlreadyAdded(_) => "already added".to_string(),
+            ExecError::SpawnFailed(_) => "spawn error".to_string(),
+            ExecError::Unauthorized => "unauthorized".to_string(),
+            ExecError::UnexpectedState(_, state) => format!("entered {}", state),
+            ExecError::Timeout(_) => "timed out".to_string(),
+            ExecError::Remote(e) => e.reason.clone(),
+            ExecError::Reported(e) => e.reason(),
+            e => e.to_string(),
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -33,6 +115,14 @@ pub enum ConfigParseError {
     DuplicatedValue(String),
     #[error("command is required in program section: {0}")]
     MissingCommand(String),
+    #[error("events is required in eventlistener section: {0}")]
+    MissingEvents(String),
+    #[error("mailto is required in notify section.")]
+    MissingRecipients,
+    #[error("port is required in inet_http_server section.")]
+    MissingPort,
+    #[error("username and password must be set together in inet_http_server section.")]
+    IncompleteCredentials,
     #[error("error: {0}")]
     Critical(String),
 }
@@ -1 +1,14 @@
 pub mod command_loop;
+pub mod commands;
+pub mod event;
+pub mod event_listener;
+pub mod hook;
+pub mod notifier;
+pub mod process;
+pub mod process_group;
+pub mod process_manager;
+pub mod process_output;
+pub mod procstat;
+pub mod registry;
+pub mod selector;
+pub mod signal;
@@ -1,16 +1,59 @@
-use crate::errors::CommandLineError::EmptyCommand;
-use crate::{commandline::CommandLine, config::runtimecontext::RuntimeContext};
+use std::sync::Arc;
 
-pub fn command_loop(rc: RuntimeContext) -> Result<(), String> {
+use serde_json::json;
+
+use crate::commandline::CommandLine;
+use crate::commandline::editor::LineEditor;
+use crate::control::client::Client;
+use crate::errors::CommandLineError::{EmptyCommand, Eof, TrailingBackslash, UnterminatedQuote};
+use crate::errors::ExecError;
+use crate::exec::commands;
+use crate::exec::registry::{Registry, Session};
+
+pub fn execute(
+    line: &CommandLine,
+    registry: &Registry,
+    client: &Client,
+    session: &Session,
+) -> Result<(), ExecError> {
+    let result = registry.dispatch(line, client, session);
+    match &result {
+        Err(ExecError::Reported(_)) | Ok(()) => {}
+        Err(e) if session.json() => {
+            println!(
+                "{}",
+                json!({"command": line.command(), "error": e.to_string(), "code": e.exit_code()})
+            );
+        }
+        Err(e @ (ExecError::UnknownCommand(_) | ExecError::Usage(_))) => eprintln!("{}", e),
+        Err(e) => eprintln!("{}: {}", line.command(), e),
+    }
+    result
+}
+
+pub fn command_loop(client: Arc<Client>, session: &Session) -> Result<(), String> {
+    let registry = Arc::new(commands::registry());
+    let mut editor =
+        LineEditor::new(Arc::clone(&registry), Arc::clone(&client)).map_err(|e| e.to_string())?;
     loop {
-        match CommandLine::readline() {
+        match editor.readline() {
             Ok(line) => {
-                println!("Command: {:?}", line);
+                let _ = execute(&line, &registry, &client, session);
+                if session.finished() {
+                    break;
+                }
             }
             Err(e) => match e {
                 EmptyCommand => {
                     continue;
                 }
+                Eof => {
+                    break;
+                }
+                UnterminatedQuote(_) | TrailingBackslash => {
+                    eprintln!("*** Syntax error: {}", e);
+                    continue;
+                }
                 _ => {
                     eprintln!("Error reading line: {}", e);
                     break;
@@ -20,3 +63,44 @@ pub fn command_loop(rc: RuntimeContext) -> Result<(), String> {
     }
     Ok(())
 }
+
+/// Runs a single command given on the command line and returns the exit status.
+pub fn run_once(args: &[String], client: &Client, session: &Session) -> i32 {
+    let Some(line) = CommandLine::from_args(args) else {
+        eprintln!("*** No command given");
+        return 1;
+    };
+    match execute(&line, &commands::registry(), client, session) {
+        Ok(()) => 0,
+        Err(e) => e.exit_code(),
+    }
+}
+
+/// Runs every command of a script in order, stopping at the first failure.
+pub fn run_script(path: &str, client: &Client, session: &Session) -> i32 {
+    let script = match std::fs::read_to_string(path) {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return 1;
+        }
+    };
+    let registry = commands::registry();
+    for (i, text) in script.lines().enumerate() {
+        let line = match CommandLine::parse(text) {
+            Ok(line) => line,
+            Err(EmptyCommand) => continue,
+            Err(e) => {
+                eprintln!("{}:{}: {}", path, i + 1, e);
+                return 1;
+            }
+        };
+        if let Err(e) = execute(&line, &registry, client, session) {
+            return e.exit_code();
+        }
+        if session.finished() {
+            break;
+        }
+    }
+    0
+}
@@ -1,17 +1,293 @@
-use std::collections::LinkedList;
+pub mod avail;
+pub mod clear;
+pub mod events;
+pub mod fg;
+pub mod help;
+pub mod json;
+pub mod pid;
+pub mod restart;
+pub mod shutdown;
+pub mod signal;
+pub mod start;
+pub mod status;
+pub mod stop;
+pub mod top;
+pub mod wait;
 
-use crate::config::config::Config;
-use crate::config::program_section::ProgramSection;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+
+use crate::control::client::Client;
 use crate::errors::ExecError;
+use crate::exec::process::ProcessState;
+use crate::exec::process_manager::TICK;
+use crate::exec::registry::{Context, Registry};
+use crate::exec::selector::Selector;
+
+pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    registry
+        .register(Box::new(avail::Avail))
+        .register(Box::new(clear::Clear))
+        .register(Box::new(events::Events))
+        .register(Box::new(fg::Fg))
+        .register(Box::new(help::Help))
+        .register(Box::new(json::Json))
+        .register(Box::new(pid::Pid))
+        .register(Box::new(restart::Restart))
+        .register(Box::new(shutdown::Shutdown))
+        .register(Box::new(signal::Signal))
+        .register(Box::new(start::Start))
+        .register(Box::new(status::Status))
+        .register(Box::new(stop::Stop))
+        .register(Box::new(top::Top))
+        .register(Box::new(wait::Wait));
+    registry
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Routes SIGINT to a flag until dropped, then restores the previous handler,
+/// so that Ctrl-C ends a long running command instead of the shell.
+struct InterruptGuard {
+    previous: libc::sigaction,
+}
+
+impl InterruptGuard {
+    fn install() -> Result<Self, ExecError> {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_interrupt as *const () as libc::sighandler_t;
+            libc::sigemptyset(&mut action.sa_mask);
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            if libc::sigaction(libc::SIGINT, &action, &mut previous) != 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            Ok(InterruptGuard { previous })
+        }
+    }
+
+    fn interrupted(&self) -> bool {
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        unsafe {
+            libc::sigaction(libc::SIGINT, &self.previous, std::ptr::null_mut());
+        }
+    }
+}
+
+/// Collects one result per target and remembers the first failure.
+/// Text lines are printed as they come; JSON is printed as one array by `finish`.
+struct Report {
+    json: bool,
+    entries: Vec<Value>,
+    failure: Option<ExecError>,
+}
+
+impl Report {
+    fn new(ctx: &Context) -> Self {
+        Report {
+            json: ctx.session.json(),
+            entries: Vec::new(),
+            failure: None,
+        }
+    }
+
+    fn add(&mut self, group: &str, name: &str, result: Result<Value, ExecError>) {
+        match result {
+            Ok(value) => {
+                if self.json {
+                    self.entries
+                        .push(json!({"name": name, "group": group, "result": value}));
+                } else {
+                    match value {
+                        Value::String(message) => {
+                            println!("{}: {}", fullname(group, name), message)
+                        }
+                        value => println!("{}: {}", fullname(group, name), value),
+                    }
+                }
+            }
+            Err(e) => {
+                if self.json {
+                    self.entries.push(json!({
+                        "name": name,
+                        "group": group,
+                        "error": e.reason(),
+                        "code": e.exit_code(),
+                    }));
+                } else {
+                    println!("{}: ERROR ({})", fullname(group, name), e.reason());
+                }
+                self.failure.get_or_insert(e);
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), ExecError> {
+        if self.json {
+            println!("{}", Value::Array(self.entries));
+        }
+        match self.failure {
+            Some(e) => Err(ExecError::Reported(Box::new(e))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn fullname(group: &str, name: &str) -> String {
+    if group == name {
+        name.to_string()
+    } else {
+        format!("{}:{}", group, name)
+    }
+}
+
+/// Asks a yes/no question on the terminal, defaulting to no.
+fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
 
-pub fn add(args: &LinkedList<String>, config: &Config) -> Result<(), ExecError> {
-    if args.len() == 0 {
-        return Err(ExecError::InvalidArgs);
+/// Shows what `all` and the glob and regex arguments matched and asks
+/// before a destructive command goes ahead; only in interactive mode.
+fn confirm_targets(ctx: &Context, args: &[String], targets: &[(String, String)]) -> bool {
+    if !ctx.session.interactive || !args.iter().any(|a| Selector::is_pattern(a)) {
+        return true;
     }
-    for programname in args.iter() {
-        config
-            .find_program(&programname)
-            .ok_or(ExecError::ProcessNameNotFound)?;
+    let names: Vec<String> = targets.iter().map(|(g, n)| fullname(g, n)).collect();
+    println!("Matched {} process(es): {}", names.len(), names.join(" "));
+    if confirm("Proceed?") {
+        return true;
+    }
+    println!("Aborted");
+    false
+}
+
+fn wait_stopped(client: &Client, targets: &[(String, String)]) -> Result<(), ExecError> {
+    while client
+        .infos(targets)?
+        .iter()
+        .any(|info| info.state == ProcessState::STOPPING)
+    {
+        thread::sleep(TICK);
     }
     Ok(())
 }
+
+/// Takes the leading `--wait` and `--timeout <secs>` options off `args`.
+fn wait_options(args: &[String]) -> Option<(bool, Option<Duration>, &[String])> {
+    let mut wait = false;
+    let mut timeout = None;
+    let mut rest = args;
+    loop {
+        match rest {
+            [flag, tail @ ..] if flag == "--wait" => {
+                wait = true;
+                rest = tail;
+            }
+            [flag, secs, tail @ ..] if flag == "--timeout" => {
+                timeout = Some(Duration::from_secs(secs.parse().ok()?));
+                rest = tail;
+            }
+            [flag, ..] if flag.starts_with("--") => return None,
+            _ => return Some((wait, timeout, rest)),
+        }
+    }
+}
+
+/// Blocks until every target is in `state`, reporting each one as it gets there.
+/// Targets that settle in another state, or are still pending at `timeout`, fail.
+fn wait_for(
+    client: &Client,
+    mut pending: Vec<(String, String)>,
+    state: ProcessState,
+    timeout: Option<Duration>,
+    report: &mut Report,
+) -> Result<(), ExecError> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    loop {
+        let infos = client.infos(&pending)?;
+        pending.clear();
+        for info in infos {
+            if info.state == state {
+                report.add(&info.group, &info.name, Ok(json!(info.state)));
+            } else if info.state.is_settled() {
+                let e = ExecError::UnexpectedState(info.name.clone(), info.state);
+                report.add(&info.group, &info.name, Err(e));
+            } else {
+                pending.push((info.group, info.name));
+            }
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            for (group, name) in pending {
+                report.add(&group, &name, Err(ExecError::Timeout(name.clone())));
+            }
+            return Ok(());
+        }
+        thread::sleep(TICK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod wait_options_tests {
+        use super::*;
+
+        fn args(line: &str) -> Vec<String> {
+            line.split_whitespace().map(String::from).collect()
+        }
+
+        #[test]
+        fn test_wait_options_none() {
+            let args = args("web");
+            let (wait, timeout, rest) = wait_options(&args).unwrap();
+            assert!(!wait);
+            assert_eq!(timeout, None);
+            assert_eq!(rest, ["web"]);
+        }
+
+        #[test]
+        fn test_wait_options_both() {
+            let args = args("--wait --timeout 30 web:* db");
+            let (wait, timeout, rest) = wait_options(&args).unwrap();
+            assert!(wait);
+            assert_eq!(timeout, Some(Duration::from_secs(30)));
+            assert_eq!(rest, ["web:*", "db"]);
+        }
+
+        #[test]
+        fn test_wait_options_invalid_timeout() {
+            assert!(wait_options(&args("--timeout soon web")).is_none());
+            assert!(wait_options(&args("--timeout")).is_none());
+        }
+
+        #[test]
+        fn test_wait_options_unknown_flag() {
+            assert!(wait_options(&args("--force web")).is_none());
+        }
+    }
+}
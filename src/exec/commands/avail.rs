@@ -0,0 +1,34 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Command, Context};
+
+use super::fullname;
+
+pub struct Avail;
+
+impl Command for Avail {
+    fn name(&self) -> &'static str {
+        "avail"
+    }
+
+    fn help(&self) -> &'static str {
+        "List every process defined in the configuration."
+    }
+
+    fn run(&self, _args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let avail = ctx.client.avail()?;
+        if ctx.session.json() {
+            println!("{}", json!(avail));
+            return Ok(());
+        }
+        for info in avail {
+            println!(
+                "{:<32} in use    {}",
+                fullname(&info.group, &info.name),
+                if info.autostart { "auto" } else { "manual" }
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use super::{Report, confirm_targets};
+
+pub struct Clear;
+
+impl Command for Clear {
+    fn name(&self) -> &'static str {
+        "clear"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::required("process", ArgKind::Process).repeated()];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Truncate the stdout and stderr logfiles of the given processes; running ones keep logging."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let targets = ctx.client.resolve(args)?;
+        if !confirm_targets(ctx, args, &targets) {
+            return Ok(());
+        }
+        let mut report = Report::new(ctx);
+        for (group, name) in targets {
+            let result = ctx.client.clear(&group, &name).map(|()| json!("cleared"));
+            report.add(&group, &name, result);
+        }
+        report.finish()
+    }
+}
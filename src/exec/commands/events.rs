@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+use std::sync::mpsc::RecvTimeoutError;
+
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::event::StateEvent;
+use crate::exec::process_manager::TICK;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use super::{InterruptGuard, fullname};
+
+pub struct Events;
+
+impl Events {
+    fn print(event: &StateEvent, json: bool) {
+        if json {
+            println!("{}", json!(event));
+            return;
+        }
+        let transition = &event.transition;
+        let mut details = Vec::new();
+        if transition.pid != 0 {
+            details.push(format!("pid {}", transition.pid));
+        }
+        if let Some(code) = transition.exitstatus {
+            details.push(format!("exit status {}", code));
+        }
+        details.push(transition.reason.clone());
+        println!(
+            "{} {} {} -> {} ({})",
+            transition.time,
+            fullname(&event.group, &event.name),
+            transition.from,
+            transition.to,
+            details.join(", ")
+        );
+    }
+}
+
+impl Command for Events {
+    fn name(&self) -> &'static str {
+        "events"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::optional("process", ArgKind::Process).repeated()];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Print process state changes as they happen, optionally only for the given processes, \
+         until Ctrl-C."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let filter: Option<HashSet<(String, String)>> = match args.is_empty() {
+            true => None,
+            false => Some(ctx.client.resolve(args)?.into_iter().collect()),
+        };
+        let events = ctx.client.events()?.responses;
+        let guard = InterruptGuard::install()?;
+        if !ctx.session.json() {
+            eprintln!("==> watching state changes, press Ctrl-C to stop");
+        }
+        while !guard.interrupted() {
+            match events.recv_timeout(TICK) {
+                Ok(event) => {
+                    let selected = filter.as_ref().is_none_or(|filter| {
+                        filter.contains(&(event.group.clone(), event.name.clone()))
+                    });
+                    if selected {
+                        Self::print(&event, ctx.session.json());
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => {
+                    eprintln!("==> taskmasterd went away");
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
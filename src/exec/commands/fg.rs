@@ -0,0 +1,78 @@
+use std::io::{self, Write};
+use std::sync::mpsc::TryRecvError;
+
+use crate::control::client::Client;
+use crate::errors::ExecError;
+use crate::exec::process_output::Stream;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use super::InterruptGuard;
+
+pub struct Fg;
+
+impl Command for Fg {
+    fn name(&self) -> &'static str {
+        "fg"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::required("process", ArgKind::Process)];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Connect the terminal to a running process: input is sent to its stdin and its \
+         output is shown until Ctrl-C detaches. The process keeps running afterwards."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        attach(&args[0], ctx.client)
+    }
+}
+
+fn attach(name: &String, client: &Client) -> Result<(), ExecError> {
+    let targets = client.resolve(std::slice::from_ref(name))?;
+    let [(group, process)] = targets.as_slice() else {
+        return Err(ExecError::InvalidArgs);
+    };
+    let session = client.attach(group, process)?;
+
+    let guard = InterruptGuard::install()?;
+    eprintln!("==> attached to {}, press Ctrl-C to detach", name);
+    let mut buf = [0u8; 4096];
+    'attached: while !guard.interrupted() {
+        loop {
+            match session.responses.try_recv() {
+                Ok((Stream::Stdout, data)) => {
+                    io::stdout().write_all(&data)?;
+                    io::stdout().flush()?;
+                }
+                Ok((Stream::Stderr, data)) => {
+                    io::stderr().write_all(&data)?;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    eprintln!("==> {} exited", name);
+                    break 'attached;
+                }
+            }
+        }
+        let mut fds = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // a timeout or EINTR just goes around to check the flag and the output again
+        if unsafe { libc::poll(&mut fds, 1, 100) } <= 0 {
+            continue;
+        }
+        let n = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+        match n {
+            0 => break,
+            n if n < 0 => continue,
+            n => Client::send_input(&session, &buf[..n as usize])?,
+        }
+    }
+    eprintln!("==> detached from {}", name);
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use serde_json::{Value, json};
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+pub struct Help;
+
+impl Command for Help {
+    fn name(&self) -> &'static str {
+        "help"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["?"]
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::optional("command", ArgKind::Command)];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Show the available commands, or the usage of a single command."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        if ctx.session.json() {
+            let commands: Vec<Value> = ctx
+                .registry
+                .commands()
+                .filter(|c| {
+                    args.is_empty()
+                        || ctx
+                            .registry
+                            .find(&args[0])
+                            .is_some_and(|f| f.name() == c.name())
+                })
+                .map(|c| {
+                    json!({
+                        "name": c.name(),
+                        "aliases": c.aliases(),
+                        "usage": c.usage(),
+                        "help": c.help(),
+                    })
+                })
+                .collect();
+            if commands.is_empty() {
+                return Err(ExecError::UnknownCommand(args[0].clone()));
+            }
+            println!("{}", Value::Array(commands));
+            return Ok(());
+        }
+        match args.first() {
+            None => {
+                println!("default commands (type help <topic>):");
+                println!("=====================================");
+                let names: Vec<&str> = ctx.registry.commands().map(|c| c.name()).collect();
+                println!("{}", names.join("  "));
+            }
+            Some(name) => {
+                let command = ctx
+                    .registry
+                    .find(name)
+                    .ok_or_else(|| ExecError::UnknownCommand(name.clone()))?;
+                println!("{}", command.usage());
+                if !command.aliases().is_empty() {
+                    println!("aliases: {}", command.aliases().join(", "));
+                }
+                println!("\t{}", command.help());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Arg, ArgKind, Command, Context, Format};
+
+pub struct Json;
+
+impl Command for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::optional("mode", ArgKind::Choice(&["on", "off"]))];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Switch command output between human readable text and JSON, or toggle it."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let format = match args.first().map(String::as_str) {
+            Some("on") => Format::Json,
+            Some("off") => Format::Text,
+            None if ctx.session.json() => Format::Text,
+            None => Format::Json,
+            Some(_) => return Err(ExecError::Usage(self.usage())),
+        };
+        ctx.session.format.set(format);
+        if ctx.session.json() {
+            println!("{}", json!({ "json": true }));
+        } else {
+            println!("json output off");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use crate::exec::selector::Selector;
+
+use super::Report;
+
+pub struct Pid;
+
+impl Command for Pid {
+    fn name(&self) -> &'static str {
+        "pid"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::optional("process", ArgKind::Process).repeated()];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Print the pid of taskmasterd, or of the given processes ('all' for every process)."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let pid = match args.first() {
+            None => ctx.client.pid()?,
+            Some(name) if args.len() == 1 && !Selector::is_pattern(name) => {
+                let targets = ctx.client.resolve(args)?;
+                let info = &ctx.client.infos(&targets)?[0];
+                if !info.state.is_running() {
+                    return Err(ExecError::NotRunning(name.clone()));
+                }
+                info.pid
+            }
+            Some(_) => {
+                let mut report = Report::new(ctx);
+                let targets = ctx.client.resolve(args)?;
+                for info in ctx.client.infos(&targets)? {
+                    let result = match info.state.is_running() {
+                        true => Ok(json!(info.pid)),
+                        false => Err(ExecError::NotRunning(info.name.clone())),
+                    };
+                    report.add(&info.group, &info.name, result);
+                }
+                return report.finish();
+            }
+        };
+        if ctx.session.json() {
+            println!("{}", json!({ "pid": pid }));
+        } else {
+            println!("{}", pid);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use super::{Report, confirm_targets, wait_stopped};
+
+pub struct Restart;
+
+impl Command for Restart {
+    fn name(&self) -> &'static str {
+        "restart"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::required("process", ArgKind::Process).repeated()];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Stop the given processes, wait for them to exit and start them again."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let targets = ctx.client.resolve(args)?;
+        if !confirm_targets(ctx, args, &targets) {
+            return Ok(());
+        }
+        for (group, name) in targets.iter() {
+            // processes which are not running are simply started
+            if let Err(e @ (ExecError::Unreachable(..) | ExecError::Io(_))) =
+                ctx.client.stop(group, name)
+            {
+                return Err(e);
+            }
+        }
+        wait_stopped(ctx.client, &targets)?;
+        let mut report = Report::new(ctx);
+        for (group, name) in targets {
+            let result = ctx.client.start(&group, &name).map(|_| json!("restarted"));
+            report.add(&group, &name, result);
+        }
+        report.finish()
+    }
+}
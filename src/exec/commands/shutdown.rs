@@ -0,0 +1,41 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Command, Context};
+
+use super::{confirm, fullname};
+
+pub struct Shutdown;
+
+impl Command for Shutdown {
+    fn name(&self) -> &'static str {
+        "shutdown"
+    }
+
+    fn help(&self) -> &'static str {
+        "Stop every process by priority, honouring stopwaitsecs, then exit taskmasterd."
+    }
+
+    fn run(&self, _args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        if ctx.session.interactive && !confirm("Really shut taskmasterd down?") {
+            println!("Shutdown aborted");
+            return Ok(());
+        }
+        let killed: Vec<String> = ctx
+            .client
+            .shutdown()?
+            .iter()
+            .map(|(group, name)| fullname(group, name))
+            .collect();
+        if ctx.session.json() {
+            println!("{}", json!({ "shutdown": true, "killed": killed }));
+        } else {
+            for name in &killed {
+                println!("{}: killed with SIGKILL after stopwaitsecs", name);
+            }
+            println!("Shut down");
+        }
+        ctx.session.finished.set(true);
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+use crate::exec::signal;
+
+use super::{Report, confirm_targets};
+
+pub struct Signal;
+
+impl Command for Signal {
+    fn name(&self) -> &'static str {
+        "signal"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 2] = [
+            Arg::required("signal", ArgKind::Signal),
+            Arg::required("process", ArgKind::Process).repeated(),
+        ];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Send a signal, by name (HUP, SIGUSR1) or number, to the given running processes."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let (signame, names) = args.split_first().unwrap();
+        let signum = signal::from_str(signame).ok_or(ExecError::InvalidSignal(signame.clone()))?;
+        let targets = ctx.client.resolve(names)?;
+        if !confirm_targets(ctx, names, &targets) {
+            return Ok(());
+        }
+        let mut report = Report::new(ctx);
+        for (group, name) in targets {
+            let result = ctx
+                .client
+                .signal(&group, &name, signum)
+                .map(|_| json!("signalled"));
+            report.add(&group, &name, result);
+        }
+        report.finish()
+    }
+}
@@ -0,0 +1,52 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::process::ProcessState;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use super::{Report, wait_for, wait_options};
+
+pub struct Start;
+
+impl Command for Start {
+    fn name(&self) -> &'static str {
+        "start"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::required("process", ArgKind::Process).repeated()];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Start the given processes; with --wait, block until they are RUNNING."
+    }
+
+    fn usage(&self) -> String {
+        "start [--wait [--timeout <secs>]] <process>...".to_string()
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let (wait, timeout, targets) =
+            wait_options(args).ok_or_else(|| ExecError::Usage(self.usage()))?;
+        if targets.is_empty() || (timeout.is_some() && !wait) {
+            return Err(ExecError::Usage(self.usage()));
+        }
+        let mut report = Report::new(ctx);
+        let mut started = Vec::new();
+        for (group, name) in ctx.client.resolve(targets)? {
+            match ctx.client.start(&group, &name) {
+                Ok(()) if wait => started.push((group, name)),
+                result => report.add(&group, &name, result.map(|_| json!("started"))),
+            }
+        }
+        wait_for(
+            ctx.client,
+            started,
+            ProcessState::RUNNING,
+            timeout,
+            &mut report,
+        )?;
+        report.finish()
+    }
+}
@@ -0,0 +1,53 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::process::{ProcessInfo, ProcessState};
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use super::fullname;
+
+pub struct Status;
+
+impl Command for Status {
+    fn name(&self) -> &'static str {
+        "status"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::optional("process", ArgKind::Process).repeated()];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Show the state of the given processes, or of every process."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let targets = if args.is_empty() {
+            ctx.client.resolve(&["all".to_string()])?
+        } else {
+            ctx.client.resolve(args)?
+        };
+        let infos: Vec<ProcessInfo> = ctx.client.infos(&targets)?;
+        if ctx.session.json() {
+            println!("{}", json!(infos));
+        } else {
+            for info in infos.iter() {
+                println!(
+                    "{:<32} {:<10} {}",
+                    fullname(&info.group, &info.name),
+                    info.state.to_string(),
+                    info.description
+                );
+            }
+        }
+        let failure = infos
+            .iter()
+            .find(|info| info.state != ProcessState::RUNNING)
+            .map(|info| ExecError::NotRunning(fullname(&info.group, &info.name)));
+        match failure {
+            Some(e) => Err(ExecError::Reported(Box::new(e))),
+            None => Ok(()),
+        }
+    }
+}
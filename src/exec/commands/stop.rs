@@ -0,0 +1,43 @@
+use serde_json::json;
+
+use crate::errors::ExecError;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use super::{Report, confirm_targets, wait_stopped};
+
+pub struct Stop;
+
+impl Command for Stop {
+    fn name(&self) -> &'static str {
+        "stop"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 1] = [Arg::required("process", ArgKind::Process).repeated()];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Stop the given processes and wait until each of them has exited."
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let targets = ctx.client.resolve(args)?;
+        if !confirm_targets(ctx, args, &targets) {
+            return Ok(());
+        }
+        let mut report = Report::new(ctx);
+        let mut stopping = Vec::new();
+        for (group, name) in targets {
+            match ctx.client.stop(&group, &name) {
+                Ok(()) => stopping.push((group, name)),
+                Err(e) => report.add(&group, &name, Err(e)),
+            }
+        }
+        wait_stopped(ctx.client, &stopping)?;
+        for (group, name) in stopping {
+            report.add(&group, &name, Ok(json!("stopped")));
+        }
+        report.finish()
+    }
+}
@@ -0,0 +1,431 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::errors::ExecError;
+use crate::exec::process::ProcessInfo;
+use crate::exec::registry::{Command, Context};
+
+use super::fullname;
+
+const REFRESH: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Name,
+    State,
+    Pid,
+    Uptime,
+    Restarts,
+    Cpu,
+    Rss,
+}
+
+impl Column {
+    const ALL: [Column; 7] = [
+        Column::Name,
+        Column::State,
+        Column::Pid,
+        Column::Uptime,
+        Column::Restarts,
+        Column::Cpu,
+        Column::Rss,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Column::Name => "NAME",
+            Column::State => "STATE",
+            Column::Pid => "PID",
+            Column::Uptime => "UPTIME",
+            Column::Restarts => "RESTARTS",
+            Column::Cpu => "CPU%",
+            Column::Rss => "RSS",
+        }
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Column::Name => 24,
+            Column::State => 10,
+            Column::Pid => 8,
+            Column::Uptime => 10,
+            Column::Restarts => 9,
+            Column::Cpu => 7,
+            Column::Rss => 9,
+        }
+    }
+
+    fn compare(&self, a: &Row, b: &Row) -> Ordering {
+        match self {
+            Column::Name => a.fullname.cmp(&b.fullname),
+            Column::State => a.info.state.to_string().cmp(&b.info.state.to_string()),
+            Column::Pid => a.info.pid.cmp(&b.info.pid),
+            Column::Uptime => a.info.uptime.cmp(&b.info.uptime),
+            Column::Restarts => a.info.restarts.cmp(&b.info.restarts),
+            Column::Cpu => a.cpu.partial_cmp(&b.cpu).unwrap_or(Ordering::Equal),
+            Column::Rss => a.rss.cmp(&b.rss),
+        }
+    }
+}
+
+struct Row {
+    fullname: String,
+    info: ProcessInfo,
+    cpu: Option<f64>,
+    rss: Option<u64>,
+}
+
+impl Row {
+    fn cell(&self, column: Column) -> String {
+        let running = self.info.state.is_running();
+        match column {
+            Column::Name => self.fullname.clone(),
+            Column::State => self.info.state.to_string(),
+            Column::Pid if running => self.info.pid.to_string(),
+            Column::Uptime if running => format_uptime(self.info.uptime),
+            Column::Restarts => self.info.restarts.to_string(),
+            Column::Cpu => self
+                .cpu
+                .map_or("-".to_string(), |cpu| format!("{:.1}", cpu)),
+            Column::Rss => self.rss.map_or("-".to_string(), format_bytes),
+            Column::Pid | Column::Uptime => "-".to_string(),
+        }
+    }
+}
+
+/// Puts the terminal in raw mode on an alternate screen until dropped.
+struct ScreenGuard;
+
+impl ScreenGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(ScreenGuard)
+    }
+}
+
+impl Drop for ScreenGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Everything the dashboard remembers between two refreshes.
+struct Dashboard {
+    sort: Column,
+    reverse: bool,
+    selected: Option<(String, String)>,
+    restarting: HashSet<(String, String)>,
+    samples: HashMap<u32, (f64, Instant)>,
+    message: String,
+}
+
+impl Dashboard {
+    fn new() -> Self {
+        Dashboard {
+            sort: Column::Name,
+            reverse: false,
+            selected: None,
+            restarting: HashSet::new(),
+            samples: HashMap::new(),
+            message: String::new(),
+        }
+    }
+
+    /// Snapshots every process, sampling CPU since the previous refresh.
+    fn rows(&mut self, ctx: &Context) -> Result<Vec<(String, String, Row)>, ExecError> {
+        let targets = ctx.client.resolve(&["all".to_string()])?;
+        let infos = ctx.client.infos(&targets)?;
+        // restarts asked for with `r` go on once the process is down
+        let mut restarting = std::mem::take(&mut self.restarting);
+        restarting.retain(|(group, name)| {
+            let info = infos.iter().find(|i| &i.group == group && &i.name == name);
+            if info.is_some_and(|info| info.state.is_running()) {
+                return true;
+            }
+            if let Err(e) = ctx.client.start(group, name) {
+                self.message = format!("{}: {}", fullname(group, name), e.reason());
+            }
+            false
+        });
+        self.restarting = restarting;
+        let now = Instant::now();
+        let mut samples = HashMap::new();
+        let mut rows: Vec<(String, String, Row)> = infos
+            .into_iter()
+            .map(|info| {
+                let usage = info.cpu.zip(info.rss);
+                let cpu = usage.and_then(|(used, _)| {
+                    samples.insert(info.pid, (used, now));
+                    let (previous, at) = self.samples.get(&info.pid)?;
+                    let elapsed = now.duration_since(*at).as_secs_f64();
+                    (elapsed > 0.0).then(|| (used - previous).max(0.0) / elapsed * 100.0)
+                });
+                let row = Row {
+                    fullname: fullname(&info.group, &info.name),
+                    cpu,
+                    rss: usage.map(|(_, rss)| rss),
+                    info,
+                };
+                (row.info.group.clone(), row.info.name.clone(), row)
+            })
+            .collect();
+        self.samples = samples;
+        rows.sort_by(|a, b| {
+            let ordering = self.sort.compare(&a.2, &b.2);
+            if self.reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        Ok(rows)
+    }
+
+    fn draw(&self, rows: &[(String, String, Row)]) -> io::Result<()> {
+        let (width, height) = match terminal::size()? {
+            // a terminal that does not report its size gets the classic one
+            (0, _) | (_, 0) => (80, 24),
+            size => size,
+        };
+        let mut out = io::stdout();
+        queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+        // two columns for the selection marker of the rows
+        let header: String = std::iter::once("  ".to_string())
+            .chain(Column::ALL.iter().map(|column| {
+                let mark = match (*column == self.sort, self.reverse) {
+                    (true, false) => "^",
+                    (true, true) => "v",
+                    (false, _) => "",
+                };
+                format!(
+                    "{:<w$}",
+                    format!("{}{}", column.title(), mark),
+                    w = column.width()
+                )
+            }))
+            .collect();
+        queue!(
+            out,
+            SetAttribute(Attribute::Reverse),
+            Print(fit(&header, width)),
+            SetAttribute(Attribute::Reset)
+        )?;
+        let visible = (height as usize).saturating_sub(3);
+        for (i, (group, name, row)) in rows.iter().take(visible).enumerate() {
+            let line: String = Column::ALL
+                .iter()
+                .map(|column| format!("{:<w$}", row.cell(*column), w = column.width()))
+                .collect();
+            let selected = self
+                .selected
+                .as_ref()
+                .is_some_and(|(g, n)| g == group && n == name);
+            queue!(out, MoveTo(0, i as u16 + 1))?;
+            if selected {
+                queue!(out, SetAttribute(Attribute::Bold), Print("> "))?;
+            } else {
+                queue!(out, Print("  "))?;
+            }
+            queue!(
+                out,
+                Print(fit(&line, width.saturating_sub(2))),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+        queue!(
+            out,
+            MoveTo(0, height.saturating_sub(2)),
+            Print(fit(&self.message, width)),
+            MoveTo(0, height.saturating_sub(1)),
+            SetAttribute(Attribute::Reverse),
+            Print(fit(
+                "up/down select  s start  x stop  r restart  1-7 sort  i invert  q quit",
+                width
+            )),
+            SetAttribute(Attribute::Reset)
+        )?;
+        out.flush()
+    }
+
+    fn select(&mut self, rows: &[(String, String, Row)], step: isize) {
+        if rows.is_empty() {
+            return;
+        }
+        let current = self
+            .selected
+            .as_ref()
+            .and_then(|(group, name)| rows.iter().position(|(g, n, _)| g == group && n == name));
+        let index = match current {
+            Some(i) => (i as isize + step).clamp(0, rows.len() as isize - 1) as usize,
+            None => 0,
+        };
+        self.selected = Some((rows[index].0.clone(), rows[index].1.clone()));
+    }
+
+    fn act(&mut self, ctx: &Context, key: char) {
+        let Some((group, name)) = self.selected.clone() else {
+            return;
+        };
+        let client = ctx.client;
+        let result = match key {
+            's' => client.start(&group, &name).map(|_| "starting"),
+            'x' => client.stop(&group, &name).map(|_| "stopping"),
+            _ => {
+                let targets = [(group.clone(), name.clone())];
+                let result = client.infos(&targets).and_then(|infos| {
+                    match infos.first().is_some_and(|info| info.state.is_running()) {
+                        true => client.stop(&group, &name),
+                        false => Ok(()),
+                    }
+                });
+                if result.is_ok() {
+                    self.restarting.insert((group.clone(), name.clone()));
+                }
+                result.map(|_| "restarting")
+            }
+        };
+        self.message = match result {
+            Ok(action) => format!("{}: {}", fullname(&group, &name), action),
+            Err(e) => format!("{}: {}", fullname(&group, &name), e.reason()),
+        };
+    }
+}
+
+pub struct Top;
+
+impl Command for Top {
+    fn name(&self) -> &'static str {
+        "top"
+    }
+
+    fn help(&self) -> &'static str {
+        "Show a live view of every process with its state, pid, uptime, restarts, CPU and RSS; \
+         start, stop and restart the selected one from the keyboard."
+    }
+
+    fn run(&self, _args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        if !io::stdout().is_terminal() || !io::stdin().is_terminal() {
+            return Err(ExecError::NotATerminal);
+        }
+        let _guard = ScreenGuard::enter()?;
+        let mut dashboard = Dashboard::new();
+        let mut next_refresh = Instant::now();
+        let mut rows = Vec::new();
+        loop {
+            if Instant::now() >= next_refresh {
+                rows = dashboard.rows(ctx)?;
+                if dashboard.selected.is_none() {
+                    dashboard.select(&rows, 0);
+                }
+                dashboard.draw(&rows)?;
+                next_refresh = Instant::now() + REFRESH;
+            }
+            if !event::poll(next_refresh.saturating_duration_since(Instant::now()))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => dashboard.select(&rows, -1),
+                KeyCode::Down | KeyCode::Char('j') => dashboard.select(&rows, 1),
+                KeyCode::Char(c @ ('s' | 'x' | 'r')) => dashboard.act(ctx, c),
+                KeyCode::Char('i') => dashboard.reverse = !dashboard.reverse,
+                KeyCode::Char(c @ '1'..='7') => {
+                    dashboard.sort = Column::ALL[c as usize - '1' as usize];
+                }
+                _ => continue,
+            }
+            // redraw right away so that the key feels responsive
+            next_refresh = Instant::now();
+        }
+    }
+}
+
+/// Cuts `line` to the terminal width.
+fn fit(line: &str, width: u16) -> String {
+    line.chars().take(width as usize).collect()
+}
+
+fn format_uptime(secs: u64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "K", "M", "G"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}{}", bytes, UNITS[0])
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod format_tests {
+        use super::*;
+
+        #[test]
+        fn test_format_uptime() {
+            assert_eq!(format_uptime(0), "0:00:00");
+            assert_eq!(format_uptime(3725), "1:02:05");
+        }
+
+        #[test]
+        fn test_format_bytes() {
+            assert_eq!(format_bytes(512), "512B");
+            assert_eq!(format_bytes(1536), "1.5K");
+            assert_eq!(format_bytes(3 * 1024 * 1024), "3.0M");
+        }
+    }
+
+    mod state_tests {
+        use super::*;
+        use crate::exec::process::ProcessState;
+
+        #[test]
+        fn test_pid_hidden_when_not_running() {
+            let row = Row {
+                fullname: "web".to_string(),
+                info: ProcessInfo {
+                    name: "web".to_string(),
+                    group: "web".to_string(),
+                    state: ProcessState::EXITED,
+                    pid: 0,
+                    uptime: 0,
+                    restarts: 2,
+                    exitstatus: Some(1),
+                    description: String::new(),
+                    cpu: None,
+                    rss: None,
+                },
+                cpu: None,
+                rss: None,
+            };
+            assert_eq!(row.cell(Column::Pid), "-");
+            assert_eq!(row.cell(Column::Restarts), "2");
+        }
+    }
+}
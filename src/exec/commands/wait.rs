@@ -0,0 +1,44 @@
+use crate::errors::ExecError;
+use crate::exec::process::ProcessState;
+use crate::exec::registry::{Arg, ArgKind, Command, Context};
+
+use super::{Report, wait_for, wait_options};
+
+pub struct Wait;
+
+impl Command for Wait {
+    fn name(&self) -> &'static str {
+        "wait"
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        const ARGS: [Arg; 2] = [
+            Arg::required("state", ArgKind::Choice(&ProcessState::NAMES)),
+            Arg::required("process", ArgKind::Process).repeated(),
+        ];
+        &ARGS
+    }
+
+    fn help(&self) -> &'static str {
+        "Block until the given processes reach a state; fails if they settle elsewhere or time out."
+    }
+
+    fn usage(&self) -> String {
+        "wait [--timeout <secs>] <state> <process>...".to_string()
+    }
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError> {
+        let usage = || ExecError::Usage(self.usage());
+        let (false, timeout, [state, targets @ ..]) = wait_options(args).ok_or_else(usage)? else {
+            return Err(usage());
+        };
+        if targets.is_empty() {
+            return Err(usage());
+        }
+        let state = ProcessState::from_str(state).ok_or_else(usage)?;
+        let targets = ctx.client.resolve(targets)?;
+        let mut report = Report::new(ctx);
+        wait_for(ctx.client, targets, state, timeout, &mut report)?;
+        report.finish()
+    }
+}
@@ -0,0 +1,43 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::exec::process::ProcessState;
+
+/// One state change of a process, recorded where it happens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub time: String,
+    pub from: ProcessState,
+    pub to: ProcessState,
+    pub pid: u32,
+    pub exitstatus: Option<i32>,
+    pub reason: String,
+}
+
+impl Transition {
+    pub fn new(
+        from: ProcessState,
+        to: ProcessState,
+        pid: u32,
+        exitstatus: Option<i32>,
+        reason: String,
+    ) -> Self {
+        Transition {
+            time: Local::now().format("%Y/%m/%d %H:%M:%S").to_string(),
+            from,
+            to,
+            pid,
+            exitstatus,
+            reason,
+        }
+    }
+}
+
+/// A transition as published to subscribers of the ProcessManager.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateEvent {
+    pub group: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub transition: Transition,
+}
@@ -0,0 +1,394 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+
+use crate::config::eventlistener::EventListener;
+use crate::config::logger::Logger;
+use crate::exec::event::StateEvent;
+use crate::exec::process::{Process, ProcessState};
+use crate::exec::process_output::Stream;
+
+/// One event, as sent to the listeners subscribed to its type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub serial: u64,
+    pub name: String,
+    pub payload: String,
+}
+
+impl Event {
+    /// A `PROCESS_STATE_*` event; `tries` and `expected` are only reported
+    /// for the states supervisord reports them for.
+    pub fn process_state(serial: u64, event: &StateEvent, tries: u8, expected: bool) -> Self {
+        let transition = &event.transition;
+        let mut payload = format!(
+            "processname:{} groupname:{} from_state:{}",
+            event.name, event.group, transition.from
+        );
+        match transition.to {
+            ProcessState::STARTING | ProcessState::BACKOFF => {
+                payload.push_str(&format!(" tries:{}", tries));
+            }
+            ProcessState::EXITED => {
+                payload.push_str(&format!(
+                    " expected:{} pid:{}",
+                    expected as u8, transition.pid
+                ));
+            }
+            ProcessState::RUNNING | ProcessState::STOPPING | ProcessState::STOPPED => {
+                payload.push_str(&format!(" pid:{}", transition.pid));
+            }
+            ProcessState::FATAL | ProcessState::UNKNOWN => {}
+        }
+        Event {
+            serial,
+            name: format!("PROCESS_STATE_{}", transition.to),
+            payload,
+        }
+    }
+
+    /// A `PROCESS_LOG_*` event carrying what a process wrote.
+    pub fn process_log(
+        serial: u64,
+        (group, name): (&str, &str),
+        pid: u32,
+        stream: Stream,
+        data: &[u8],
+    ) -> Self {
+        let channel = match stream {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        };
+        Event {
+            serial,
+            name: format!("PROCESS_LOG_{}", channel.to_uppercase()),
+            payload: format!(
+                "processname:{} groupname:{} pid:{} channel:{}\n{}",
+                name,
+                group,
+                pid,
+                channel,
+                String::from_utf8_lossy(data)
+            ),
+        }
+    }
+
+    /// A `TICK_{period}` event sent when the clock crosses a multiple of `period`.
+    pub fn tick(serial: u64, period: u64, when: u64) -> Self {
+        Event {
+            serial,
+            name: format!("TICK_{}", period),
+            payload: format!("when:{}", when),
+        }
+    }
+
+    /// `SUPERVISOR_STATE_CHANGE_RUNNING` or `SUPERVISOR_STATE_CHANGE_STOPPING`.
+    pub fn supervisor_state(serial: u64, state: &str) -> Self {
+        Event {
+            serial,
+            name: format!("SUPERVISOR_STATE_CHANGE_{}", state),
+            payload: String::new(),
+        }
+    }
+
+    /// Whether a listener subscribed to `events` receives this event: a type
+    /// matches itself and its subtypes, and `EVENT` matches everything.
+    pub fn matches(&self, events: &[String]) -> bool {
+        events.iter().any(|event| {
+            event == "EVENT"
+                || *event == self.name
+                || self
+                    .name
+                    .strip_prefix(event.as_str())
+                    .is_some_and(|rest| rest.starts_with('_'))
+        })
+    }
+
+    /// The header line and payload written to a listener's stdin.
+    pub fn serialize(&self, pool: &str, poolserial: u64) -> Vec<u8> {
+        format!(
+            "ver:3.0 server:taskmaster serial:{} pool:{} poolserial:{} eventname:{} len:{}\n{}",
+            self.serial,
+            pool,
+            poolserial,
+            self.name,
+            self.payload.len(),
+            self.payload
+        )
+        .into_bytes()
+    }
+}
+
+/// What a listener told us on its stdout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reply {
+    Ready,
+    /// The body of a `RESULT` reply: `OK` or `FAIL`.
+    Result(String),
+    /// Bytes that are not part of the protocol.
+    Garbage(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListenerState {
+    /// Waiting for `READY`.
+    Acknowledged,
+    Ready,
+    /// Processing an event, waiting for its `RESULT`.
+    Busy,
+}
+
+/// Takes the next complete reply off the bytes a listener wrote, or None
+/// until more of them arrive.
+fn next_reply(pending: &mut Vec<u8>, state: ListenerState) -> Option<Reply> {
+    let newline = pending.iter().position(|&b| b == b'\n')?;
+    let line = String::from_utf8_lossy(&pending[..newline]).into_owned();
+    let reply = match (state, line.split_once(' ')) {
+        (ListenerState::Acknowledged, _) if line == "READY" => {
+            pending.drain(..=newline);
+            Reply::Ready
+        }
+        (ListenerState::Busy, Some(("RESULT", len))) => match len.parse::<usize>() {
+            Ok(len) if pending.len() > newline + len => {
+                let body = &pending[newline + 1..=newline + len];
+                let body = String::from_utf8_lossy(body).into_owned();
+                pending.drain(..=newline + len);
+                Reply::Result(body)
+            }
+            Ok(_) => return None,
+            Err(_) => {
+                pending.drain(..=newline);
+                Reply::Garbage(line)
+            }
+        },
+        _ => {
+            pending.drain(..=newline);
+            Reply::Garbage(line)
+        }
+    };
+    Some(reply)
+}
+
+/// The protocol state of one listener process.
+#[derive(Debug)]
+struct Listener {
+    pid: u32,
+    state: ListenerState,
+    pending: Vec<u8>,
+    /// The event the listener is processing.
+    event: Option<Event>,
+}
+
+impl Listener {
+    fn new(pid: u32) -> Self {
+        Listener {
+            pid,
+            state: ListenerState::Acknowledged,
+            pending: Vec::new(),
+            event: None,
+        }
+    }
+}
+
+/// The events waiting for the processes of an event listener program, and
+/// where each of those processes is in the protocol.
+#[derive(Debug)]
+pub struct EventPool {
+    name: String,
+    events: Vec<String>,
+    buffer_size: usize,
+    buffer: VecDeque<Event>,
+    poolserial: u64,
+    listeners: HashMap<String, Listener>,
+}
+
+impl EventPool {
+    pub fn new(name: &str, listener: &EventListener) -> Self {
+        EventPool {
+            name: name.to_string(),
+            events: listener.events().clone(),
+            buffer_size: listener.buffer_size(),
+            buffer: VecDeque::new(),
+            poolserial: 0,
+            listeners: HashMap::new(),
+        }
+    }
+
+    /// Queues `event` if the pool subscribed to it, dropping the oldest
+    /// event when the buffer is full.
+    pub fn push(&mut self, event: &Event, logger: &Logger) {
+        if !event.matches(&self.events) {
+            return;
+        }
+        if self.buffer.len() >= self.buffer_size
+            && let Some(dropped) = self.buffer.pop_front()
+        {
+            logger.warn(&format!(
+                "pool {} event buffer overflowed, discarding event {}",
+                self.name, dropped.serial
+            ));
+        }
+        self.buffer.push_back(event.clone());
+    }
+
+    /// Reads what the listener processes replied and sends the buffered
+    /// events to the ones that are ready for one.
+    pub fn dispatch(&mut self, processes: &HashMap<String, Process>, logger: &Logger) {
+        for (name, process) in processes {
+            let pid = match process.state().is_running() {
+                true => process.pid(),
+                false => 0,
+            };
+            if self.listeners.get(name).is_some_and(|l| l.pid != pid)
+                && let Some(listener) = self.listeners.remove(name)
+                && let Some(event) = listener.event
+            {
+                // the listener died with it, someone else gets it
+                self.buffer.push_front(event);
+            }
+            if pid == 0 {
+                continue;
+            }
+            let listener = self
+                .listeners
+                .entry(name.clone())
+                .or_insert_with(|| Listener::new(pid));
+            listener.pending.extend(process.take_protocol_output());
+            while let Some(reply) = next_reply(&mut listener.pending, listener.state) {
+                match reply {
+                    Reply::Ready => listener.state = ListenerState::Ready,
+                    Reply::Result(body) => {
+                        let event = listener.event.take();
+                        if body != "OK"
+                            && let Some(event) = event
+                        {
+                            logger.warn(&format!(
+                                "{}: event {} rejected with {:?}, requeueing",
+                                name, event.serial, body
+                            ));
+                            self.buffer.push_front(event);
+                        }
+                        listener.state = ListenerState::Acknowledged;
+                    }
+                    Reply::Garbage(line) => {
+                        logger.warn(&format!("{}: unexpected protocol output {:?}", name, line));
+                    }
+                }
+            }
+            if listener.state != ListenerState::Ready {
+                continue;
+            }
+            let (Some(stdin), Some(event)) = (process.stdin(), self.buffer.pop_front()) else {
+                continue;
+            };
+            self.poolserial += 1;
+            let bytes = event.serialize(&self.name, self.poolserial);
+            match stdin.lock().unwrap().write_all(&bytes) {
+                Ok(()) => {
+                    listener.state = ListenerState::Busy;
+                    listener.event = Some(event);
+                }
+                Err(e) => {
+                    logger.warn(&format!("{}: cannot send event: {}", name, e));
+                    self.buffer.push_front(event);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::event::Transition;
+
+    mod event_tests {
+        use super::*;
+
+        fn event(name: &str) -> Event {
+            Event {
+                serial: 1,
+                name: name.to_string(),
+                payload: String::new(),
+            }
+        }
+
+        #[test]
+        fn test_matches_subtypes() {
+            let events = vec!["PROCESS_STATE".to_string()];
+            assert!(event("PROCESS_STATE_EXITED").matches(&events));
+            assert!(!event("PROCESS_LOG_STDOUT").matches(&events));
+            assert!(event("TICK_5").matches(&["EVENT".to_string()]));
+            assert!(!event("TICK_5").matches(&["TICK_60".to_string()]));
+        }
+
+        #[test]
+        fn test_process_state_payload() {
+            let state = StateEvent {
+                group: "web".to_string(),
+                name: "web_0".to_string(),
+                transition: Transition::new(
+                    ProcessState::RUNNING,
+                    ProcessState::EXITED,
+                    42,
+                    Some(1),
+                    String::new(),
+                ),
+            };
+            let event = Event::process_state(7, &state, 0, false);
+            assert_eq!(event.name, "PROCESS_STATE_EXITED");
+            assert_eq!(
+                event.payload,
+                "processname:web_0 groupname:web from_state:RUNNING expected:0 pid:42"
+            );
+        }
+
+        #[test]
+        fn test_serialize() {
+            let event = Event::tick(3, 5, 1700000000);
+            assert_eq!(
+                String::from_utf8(event.serialize("crashmail", 2)).unwrap(),
+                "ver:3.0 server:taskmaster serial:3 pool:crashmail poolserial:2 eventname:TICK_5 len:15\nwhen:1700000000"
+            );
+        }
+    }
+
+    mod next_reply_tests {
+        use super::*;
+
+        #[test]
+        fn test_ready() {
+            let mut pending = b"READY\n".to_vec();
+            assert_eq!(
+                next_reply(&mut pending, ListenerState::Acknowledged),
+                Some(Reply::Ready)
+            );
+            assert!(pending.is_empty());
+        }
+
+        #[test]
+        fn test_result_waits_for_body() {
+            let mut pending = b"RESULT 2\nO".to_vec();
+            assert_eq!(next_reply(&mut pending, ListenerState::Busy), None);
+            pending.extend(b"KREADY\n");
+            assert_eq!(
+                next_reply(&mut pending, ListenerState::Busy),
+                Some(Reply::Result("OK".to_string()))
+            );
+            assert_eq!(pending, b"READY\n");
+        }
+
+        #[test]
+        fn test_garbage() {
+            let mut pending = b"hello\n".to_vec();
+            assert_eq!(
+                next_reply(&mut pending, ListenerState::Busy),
+                Some(Reply::Garbage("hello".to_string()))
+            );
+            let mut pending = b"READY\n".to_vec();
+            assert_eq!(
+                next_reply(&mut pending, ListenerState::Busy),
+                Some(Reply::Garbage("READY".to_string()))
+            );
+        }
+    }
+}
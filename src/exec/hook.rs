@@ -0,0 +1,173 @@
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::config::logger::Logger;
+use crate::config::program::{Program, program};
+use crate::exec::event::StateEvent;
+use crate::exec::process::ProcessState;
+use crate::exec::process_manager::TICK;
+
+/// The hook `program` runs when one of its processes enters `state`, and
+/// the key it was configured with.
+pub fn for_state(program: &Program, state: ProcessState) -> Option<(&'static str, &String)> {
+    match state {
+        ProcessState::RUNNING => Some((program::ON_START, program.on_start()?)),
+        ProcessState::EXITED => Some((program::ON_EXIT, program.on_exit()?)),
+        ProcessState::BACKOFF => Some((program::ON_BACKOFF, program.on_backoff()?)),
+        ProcessState::FATAL => Some((program::ON_FATAL, program.on_fatal()?)),
+        _ => None,
+    }
+}
+
+/// The variables describing `event` to a hook.
+fn environment(key: &str, event: &StateEvent) -> Vec<(&'static str, String)> {
+    let transition = &event.transition;
+    let mut environment = vec![
+        ("TASKMASTER_HOOK", key.to_string()),
+        ("TASKMASTER_PROCESS_NAME", event.name.clone()),
+        ("TASKMASTER_GROUP_NAME", event.group.clone()),
+        ("TASKMASTER_FROM_STATE", transition.from.to_string()),
+        ("TASKMASTER_STATE", transition.to.to_string()),
+        ("TASKMASTER_PID", transition.pid.to_string()),
+        ("TASKMASTER_REASON", transition.reason.clone()),
+    ];
+    if let Some(code) = transition.exitstatus {
+        environment.push(("TASKMASTER_EXIT_STATUS", code.to_string()));
+    }
+    environment
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Runs `command` with the shell in the background, killing it after
+/// `timeout`; what it prints ends up in the daemon log.
+pub fn run(
+    key: &'static str,
+    command: &str,
+    event: &StateEvent,
+    timeout: Duration,
+    logger: Logger,
+) {
+    let name = format!("{}:{}", event.group, event.name);
+    let mut child = match Command::new("/bin/sh")
+        .arg("-c")
+        .arg(command)
+        .envs(environment(key, event))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // its own group, so that whatever it starts is killed with it
+        .process_group(0)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return logger.error(&format!("{} hook of {}: {}", key, name, e)),
+    };
+    logger.debug(&format!(
+        "{} hook of {} started with pid {}",
+        key,
+        name,
+        child.id()
+    ));
+    thread::spawn(move || {
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if Instant::now() < deadline => thread::sleep(TICK),
+                Ok(None) => {
+                    logger.warn(&format!(
+                        "{} hook of {} timed out after {} seconds, killing it",
+                        key,
+                        name,
+                        timeout.as_secs()
+                    ));
+                    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+                    let _ = child.wait();
+                    break None;
+                }
+                Err(e) => {
+                    logger.error(&format!("{} hook of {}: {}", key, name, e));
+                    break None;
+                }
+            }
+        };
+        for (output, error) in [(stdout, false), (stderr, true)] {
+            let output = output.join().unwrap_or_default();
+            for line in String::from_utf8_lossy(&output).lines() {
+                let message = format!("{} hook of {}: {}", key, name, line);
+                match error {
+                    true => logger.warn(&message),
+                    false => logger.info(&message),
+                }
+            }
+        }
+        if let Some(status) = status.filter(|status| !status.success()) {
+            logger.warn(&format!("{} hook of {} failed: {}", key, name, status));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::event::Transition;
+
+    mod for_state_tests {
+        use super::*;
+
+        #[test]
+        fn test_for_state() {
+            let mut builder = Program::builder();
+            builder
+                .programname("web".to_string())
+                .command(["true".to_string()].into())
+                .on_fatal("alert".to_string());
+            let program = builder.build().unwrap();
+            assert_eq!(
+                for_state(&program, ProcessState::FATAL),
+                Some((program::ON_FATAL, &"alert".to_string()))
+            );
+            assert_eq!(for_state(&program, ProcessState::EXITED), None);
+            assert_eq!(for_state(&program, ProcessState::STOPPED), None);
+        }
+    }
+
+    mod environment_tests {
+        use super::*;
+
+        #[test]
+        fn test_environment() {
+            let event = StateEvent {
+                group: "web".to_string(),
+                name: "web_0".to_string(),
+                transition: Transition::new(
+                    ProcessState::RUNNING,
+                    ProcessState::EXITED,
+                    42,
+                    Some(3),
+                    "unexpected exit".to_string(),
+                ),
+            };
+            let environment = environment(program::ON_EXIT, &event);
+            assert!(environment.contains(&("TASKMASTER_HOOK", "on_exit".to_string())));
+            assert!(environment.contains(&("TASKMASTER_FROM_STATE", "RUNNING".to_string())));
+            assert!(environment.contains(&("TASKMASTER_STATE", "EXITED".to_string())));
+            assert!(environment.contains(&("TASKMASTER_PID", "42".to_string())));
+            assert!(environment.contains(&("TASKMASTER_EXIT_STATUS", "3".to_string())));
+        }
+    }
+}
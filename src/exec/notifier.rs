@@ -0,0 +1,389 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::TcpStream;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use crate::config::logger::Logger;
+use crate::config::notify::Notify;
+use crate::config::program::Program;
+use crate::exec::event::StateEvent;
+use crate::exec::process::ProcessState;
+
+/// How much of the end of the stderr log is searched for the quoted lines.
+const STDERR_TAIL_BYTES: u64 = 64 * 1024;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Mails the `[notify]` recipients when a process goes FATAL or keeps
+/// restarting.
+#[derive(Debug)]
+pub struct Notifier {
+    config: Notify,
+    /// When each process was restarted, within the restart window.
+    restarts: HashMap<(String, String), VecDeque<Instant>>,
+}
+
+impl Notifier {
+    pub fn new(config: &Notify) -> Self {
+        Notifier {
+            config: config.clone(),
+            restarts: HashMap::new(),
+        }
+    }
+
+    /// Sends a mail about `event` if someone should hear about it.
+    pub fn observe(&mut self, event: &StateEvent, program: &Program, logger: &Logger) {
+        let transition = &event.transition;
+        let subject = match (transition.from, transition.to) {
+            (_, ProcessState::FATAL) => "entered FATAL".to_string(),
+            (ProcessState::EXITED | ProcessState::BACKOFF, ProcessState::STARTING) => {
+                match self.restarted(event) {
+                    Some(count) => format!(
+                        "restarted {} times in {} seconds",
+                        count,
+                        self.config.restart_window()
+                    ),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let subject = format!(
+            "[taskmaster] {}:{} {} on {}",
+            event.group,
+            event.name,
+            subject,
+            hostname()
+        );
+        let stderr = read_tail(program.stderr_logfile(), self.config.stderr_lines());
+        let body = summary(event, program.stderr_logfile(), &stderr);
+        let message = compose(self.config.from(), self.config.mailto(), &subject, &body);
+        let config = self.config.clone();
+        let logger = logger.clone();
+        thread::spawn(move || {
+            let result = match config.smtp_host() {
+                Some(host) => smtp(
+                    (host, config.smtp_port()),
+                    config.from(),
+                    config.mailto(),
+                    &message,
+                ),
+                None => sendmail(config.sendmail(), &message),
+            };
+            match result {
+                Ok(()) => logger.info(&format!(
+                    "mailed {}: {}",
+                    config.mailto().join(", "),
+                    subject
+                )),
+                Err(e) => logger.error(&format!("cannot mail {}: {}", subject, e)),
+            }
+        });
+    }
+
+    fn restarted(&mut self, event: &StateEvent) -> Option<usize> {
+        let max_restarts = self.config.max_restarts()?;
+        let window = Duration::from_secs(self.config.restart_window());
+        let restarts = self
+            .restarts
+            .entry((event.group.clone(), event.name.clone()))
+            .or_default();
+        record_restart(restarts, Instant::now(), max_restarts, window)
+    }
+}
+
+/// Records a restart at `now`; once there were more than `max_restarts`
+/// within `window`, returns how many and starts counting again.
+fn record_restart(
+    restarts: &mut VecDeque<Instant>,
+    now: Instant,
+    max_restarts: u32,
+    window: Duration,
+) -> Option<usize> {
+    restarts.push_back(now);
+    while restarts
+        .front()
+        .is_some_and(|t| now.duration_since(*t) > window)
+    {
+        restarts.pop_front();
+    }
+    if restarts.len() <= max_restarts as usize {
+        return None;
+    }
+    let count = restarts.len();
+    restarts.clear();
+    Some(count)
+}
+
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+        return "localhost".to_string();
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+/// The last `lines` lines of the file at `path`, or nothing if it cannot be read.
+fn read_tail(path: &str, lines: usize) -> String {
+    let mut data = Vec::new();
+    let read = File::open(path).and_then(|mut file| {
+        let size = file.metadata()?.len();
+        file.seek(SeekFrom::Start(size.saturating_sub(STDERR_TAIL_BYTES)))?;
+        file.read_to_end(&mut data)
+    });
+    match read {
+        Ok(_) => last_lines(&String::from_utf8_lossy(&data), lines),
+        Err(_) => String::new(),
+    }
+}
+
+fn last_lines(text: &str, lines: usize) -> String {
+    let all: Vec<&str> = text.lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+fn summary(event: &StateEvent, stderr_logfile: &str, stderr: &str) -> String {
+    let transition = &event.transition;
+    let mut body = format!(
+        "process:     {}:{}\nstate:       {} -> {}\nreason:      {}\ntime:        {}\npid:         {}\n",
+        event.group,
+        event.name,
+        transition.from,
+        transition.to,
+        transition.reason,
+        transition.time,
+        transition.pid,
+    );
+    if let Some(code) = transition.exitstatus {
+        body.push_str(&format!("exit status: {}\n", code));
+    }
+    match stderr.is_empty() {
+        true => body.push_str(&format!("\nnothing in {}\n", stderr_logfile)),
+        false => body.push_str(&format!(
+            "\nlast lines of {}:\n\n{}\n",
+            stderr_logfile, stderr
+        )),
+    }
+    body
+}
+
+/// A plain text mail, with CRLF line endings.
+fn compose(from: &str, mailto: &[String], subject: &str, body: &str) -> String {
+    let headers = [
+        format!("From: {}", from),
+        format!("To: {}", mailto.join(", ")),
+        format!("Subject: {}", subject),
+        format!("Date: {}", Local::now().to_rfc2822()),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+    ];
+    let mut message = headers.join("\r\n");
+    message.push_str("\r\n\r\n");
+    for line in body.lines() {
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message
+}
+
+fn sendmail(command: &[String], message: &str) -> io::Result<()> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| io::Error::other("no sendmail command"))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+    let status = child.wait()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(io::Error::other(format!("{} {}", program, status))),
+    }
+}
+
+/// Reads a reply, continuation lines included, and checks that its code is
+/// in the class of `expected`.
+fn expect<R: BufRead>(reader: &mut R, expected: u16) -> io::Result<()> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "SMTP server hung up",
+            ));
+        }
+        let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+        match code {
+            Some(_) if line.as_bytes().get(3) == Some(&b'-') => continue,
+            Some(code) if code / 100 == expected / 100 => return Ok(()),
+            _ => return Err(io::Error::other(format!("SMTP: {}", line.trim_end()))),
+        }
+    }
+}
+
+fn smtp(address: (&str, u16), from: &str, mailto: &[String], message: &str) -> io::Result<()> {
+    let stream = TcpStream::connect(address)?;
+    stream.set_read_timeout(Some(SMTP_TIMEOUT))?;
+    stream.set_write_timeout(Some(SMTP_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    expect(&mut reader, 220)?;
+    let mut command = |line: &str, expected: u16| -> io::Result<()> {
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\r\n")?;
+        expect(&mut reader, expected)
+    };
+    command(&format!("HELO {}", hostname()), 250)?;
+    command(&format!("MAIL FROM:<{}>", from), 250)?;
+    for rcpt in mailto {
+        command(&format!("RCPT TO:<{}>", rcpt), 250)?;
+    }
+    command("DATA", 354)?;
+    // a line starting with a dot gets another one, the lone dot ends the data
+    let data = message.replace("\r\n.", "\r\n..");
+    command(&format!("{}.", data), 250)?;
+    let _ = command("QUIT", 221);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::event::Transition;
+    use std::net::TcpListener;
+
+    fn event(from: ProcessState, to: ProcessState) -> StateEvent {
+        StateEvent {
+            group: "web".to_string(),
+            name: "web_0".to_string(),
+            transition: Transition::new(from, to, 42, Some(1), "exited too quickly".to_string()),
+        }
+    }
+
+    mod record_restart_tests {
+        use super::*;
+
+        const WINDOW: Duration = Duration::from_secs(60);
+
+        #[test]
+        fn test_record_restart_over_threshold() {
+            let mut restarts = VecDeque::new();
+            let start = Instant::now();
+            assert_eq!(record_restart(&mut restarts, start, 2, WINDOW), None);
+            assert_eq!(record_restart(&mut restarts, start, 2, WINDOW), None);
+            assert_eq!(record_restart(&mut restarts, start, 2, WINDOW), Some(3));
+            // counting starts again after a mail
+            assert_eq!(record_restart(&mut restarts, start, 2, WINDOW), None);
+        }
+
+        #[test]
+        fn test_record_restart_outside_window() {
+            let mut restarts = VecDeque::new();
+            let start = Instant::now();
+            for i in 0..5 {
+                let now = start + Duration::from_secs(i * 61);
+                assert_eq!(record_restart(&mut restarts, now, 2, WINDOW), None);
+            }
+        }
+    }
+
+    mod message_tests {
+        use super::*;
+
+        #[test]
+        fn test_last_lines() {
+            assert_eq!(last_lines("a\nb\nc\n", 2), "b\nc");
+            assert_eq!(last_lines("a\n", 5), "a");
+            assert_eq!(last_lines("", 5), "");
+        }
+
+        #[test]
+        fn test_summary() {
+            let body = summary(
+                &event(ProcessState::STARTING, ProcessState::FATAL),
+                "/tmp/web.err",
+                "boom",
+            );
+            assert!(body.contains("process:     web:web_0\n"));
+            assert!(body.contains("state:       STARTING -> FATAL\n"));
+            assert!(body.contains("exit status: 1\n"));
+            assert!(body.ends_with("last lines of /tmp/web.err:\n\nboom\n"));
+        }
+
+        #[test]
+        fn test_compose() {
+            let to = vec!["a@example.com".to_string(), "b@example.com".to_string()];
+            let message = compose("t@example.com", &to, "down", "line\n.dot");
+            assert!(message.starts_with(
+                "From: t@example.com\r\nTo: a@example.com, b@example.com\r\nSubject: down\r\n"
+            ));
+            assert!(message.ends_with("\r\n\r\nline\r\n.dot\r\n"));
+        }
+    }
+
+    mod smtp_tests {
+        use super::*;
+
+        /// Plays an SMTP server accepting one mail, and returns what it was sent.
+        fn stand_in() -> (u16, thread::JoinHandle<String>) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = thread::spawn(move || {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut writer = stream;
+                let mut received = String::new();
+                writer.write_all(b"220 stand-in ESMTP\r\n").unwrap();
+                let mut data = false;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 {
+                        break;
+                    }
+                    received.push_str(&line);
+                    let reply: &[u8] = match line.as_str() {
+                        ".\r\n" if data => {
+                            data = false;
+                            b"250 queued\r\n"
+                        }
+                        _ if data => continue,
+                        "DATA\r\n" => {
+                            data = true;
+                            b"354 go ahead\r\n"
+                        }
+                        "QUIT\r\n" => {
+                            writer.write_all(b"221 bye\r\n").unwrap();
+                            break;
+                        }
+                        _ => b"250-stand-in\r\n250 ok\r\n",
+                    };
+                    writer.write_all(reply).unwrap();
+                }
+                received
+            });
+            (port, server)
+        }
+
+        #[test]
+        fn test_smtp_delivers() {
+            let (port, server) = stand_in();
+            let to = vec!["ops@example.com".to_string()];
+            let message = compose("t@example.com", &to, "down", "hello\n.dot");
+            smtp(("127.0.0.1", port), "t@example.com", &to, &message).unwrap();
+            let received = server.join().unwrap();
+            assert!(received.contains("MAIL FROM:<t@example.com>\r\n"));
+            assert!(received.contains("RCPT TO:<ops@example.com>\r\n"));
+            assert!(received.contains("\r\nhello\r\n..dot\r\n.\r\n"));
+            assert!(received.ends_with("QUIT\r\n"));
+        }
+    }
+}
@@ -1,7 +1,24 @@
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::logger::Logger;
+use crate::config::program::{Program, program::AutoRestart};
+use crate::errors::ExecError;
+use crate::exec::event::Transition;
+use crate::exec::process_output::{ProcessOutput, Stream};
+use crate::exec::procstat;
+use crate::exec::signal;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProcessState {
     STOPPED,
     STARTING,
+    RUNNING,
     BACKOFF,
     STOPPING,
     EXITED,
@@ -9,9 +26,521 @@ pub enum ProcessState {
     UNKNOWN,
 }
 
+impl std::fmt::Display for ProcessState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessState::STOPPED => write!(f, "STOPPED"),
+            ProcessState::STARTING => write!(f, "STARTING"),
+            ProcessState::RUNNING => write!(f, "RUNNING"),
+            ProcessState::BACKOFF => write!(f, "BACKOFF"),
+            ProcessState::STOPPING => write!(f, "STOPPING"),
+            ProcessState::EXITED => write!(f, "EXITED"),
+            ProcessState::FATAL => write!(f, "FATAL"),
+            ProcessState::UNKNOWN => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+impl ProcessState {
+    /// The names `wait` accepts, for tab completion.
+    pub const NAMES: [&str; 7] = [
+        "STOPPED", "STARTING", "RUNNING", "BACKOFF", "STOPPING", "EXITED", "FATAL",
+    ];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_uppercase().as_str() {
+            "STOPPED" => Some(ProcessState::STOPPED),
+            "STARTING" => Some(ProcessState::STARTING),
+            "RUNNING" => Some(ProcessState::RUNNING),
+            "BACKOFF" => Some(ProcessState::BACKOFF),
+            "STOPPING" => Some(ProcessState::STOPPING),
+            "EXITED" => Some(ProcessState::EXITED),
+            "FATAL" => Some(ProcessState::FATAL),
+            _ => None,
+        }
+    }
+
+    /// States a process does not leave without someone starting it again.
+    pub fn is_settled(&self) -> bool {
+        matches!(
+            self,
+            ProcessState::STOPPED | ProcessState::EXITED | ProcessState::FATAL
+        )
+    }
+
+    /// The numeric state supervisord reports over XML-RPC.
+    pub fn code(&self) -> i32 {
+        match self {
+            ProcessState::STOPPED => 0,
+            ProcessState::STARTING => 10,
+            ProcessState::RUNNING => 20,
+            ProcessState::BACKOFF => 30,
+            ProcessState::STOPPING => 40,
+            ProcessState::EXITED => 100,
+            ProcessState::FATAL => 200,
+            ProcessState::UNKNOWN => 1000,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(
+            self,
+            ProcessState::STARTING | ProcessState::RUNNING | ProcessState::STOPPING
+        )
+    }
+}
+
+/// A snapshot of a process for status reporting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub name: String,
+    pub group: String,
+    pub state: ProcessState,
+    pub pid: u32,
+    pub uptime: u64,
+    pub restarts: u32,
+    pub exitstatus: Option<i32>,
+    pub description: String,
+    /// CPU seconds used so far, while running.
+    pub cpu: Option<f64>,
+    /// Resident set size in bytes, while running.
+    pub rss: Option<u64>,
+}
+
 #[derive(Debug)]
 pub struct Process {
     pid: u32,
     name: String,
     state: ProcessState,
+    child: Option<Child>,
+    stdin: Option<Arc<Mutex<ChildStdin>>>,
+    stdout: Arc<Mutex<ProcessOutput>>,
+    stderr: Arc<Mutex<ProcessOutput>>,
+    retries: u8,
+    started_at: Option<Instant>,
+    stopping_since: Option<Instant>,
+    backoff_until: Option<Instant>,
+    exitstatus: Option<i32>,
+    killed: bool,
+    restarts: u32,
+    last_pid: u32,
+    transitions: Vec<Transition>,
+    failed_starts: u32,
+    protocol: Option<Receiver<(Stream, Vec<u8>)>>, // stdout of an event listener
+    log_events: Option<Receiver<(Stream, Vec<u8>)>>, // output sent as PROCESS_LOG events
+}
+
+impl Process {
+    pub fn new(name: String) -> Self {
+        Process {
+            pid: 0,
+            name,
+            state: ProcessState::STOPPED,
+            child: None,
+            stdin: None,
+            stdout: Arc::new(Mutex::new(ProcessOutput::without_logfile(Stream::Stdout))),
+            stderr: Arc::new(Mutex::new(ProcessOutput::without_logfile(Stream::Stderr))),
+            retries: 0,
+            started_at: None,
+            stopping_since: None,
+            backoff_until: None,
+            exitstatus: None,
+            killed: false,
+            restarts: 0,
+            last_pid: 0,
+            transitions: Vec::new(),
+            failed_starts: 0,
+            protocol: None,
+            log_events: None,
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn state(&self) -> ProcessState {
+        self.state
+    }
+
+    /// Hands over the state changes recorded since the last call.
+    pub fn take_transitions(&mut self) -> Vec<Transition> {
+        std::mem::take(&mut self.transitions)
+    }
+
+    /// Start attempts since the last successful start.
+    pub fn retries(&self) -> u8 {
+        self.retries
+    }
+
+    /// Hands over what an event listener wrote on stdout since the last call.
+    pub fn take_protocol_output(&self) -> Vec<u8> {
+        self.protocol
+            .iter()
+            .flat_map(|rx| rx.try_iter())
+            .flat_map(|(_, data)| data)
+            .collect()
+    }
+
+    /// Hands over the output to send as PROCESS_LOG events since the last call.
+    pub fn take_log_output(&self) -> Vec<(Stream, Vec<u8>)> {
+        self.log_events
+            .iter()
+            .flat_map(|rx| rx.try_iter())
+            .collect()
+    }
+
+    /// Hands over the number of starts that failed since the last call.
+    pub fn take_failed_starts(&mut self) -> u32 {
+        std::mem::take(&mut self.failed_starts)
+    }
+
+    fn set_state(&mut self, state: ProcessState, reason: impl Into<String>) {
+        if state == self.state {
+            return;
+        }
+        self.transitions.push(Transition::new(
+            self.state,
+            state,
+            self.last_pid,
+            self.exitstatus,
+            reason.into(),
+        ));
+        self.state = state;
+    }
+
+    /// Whether the last stop had to be escalated to SIGKILL.
+    pub fn was_killed(&self) -> bool {
+        self.killed
+    }
+
+    pub fn info(&self, group: &str) -> ProcessInfo {
+        let usage = match self.state.is_running() {
+            true => procstat::usage(self.pid),
+            false => None,
+        };
+        ProcessInfo {
+            name: self.name.clone(),
+            group: group.to_string(),
+            state: self.state,
+            pid: self.pid,
+            uptime: match self.state {
+                ProcessState::RUNNING | ProcessState::STOPPING => {
+                    self.started_at.map_or(0, |t| t.elapsed().as_secs())
+                }
+                _ => 0,
+            },
+            restarts: self.restarts,
+            exitstatus: self.exitstatus,
+            description: self.description(),
+            cpu: usage.map(|usage| usage.cpu.as_secs_f64()),
+            rss: usage.map(|usage| usage.rss),
+        }
+    }
+
+    /// The one-line summary shown next to the state by `status`.
+    pub fn description(&self) -> String {
+        match self.state {
+            ProcessState::RUNNING | ProcessState::STOPPING => {
+                let uptime = self.started_at.map_or(0, |t| t.elapsed().as_secs());
+                format!(
+                    "pid {}, uptime {}:{:02}:{:02}",
+                    self.pid,
+                    uptime / 3600,
+                    uptime / 60 % 60,
+                    uptime % 60
+                )
+            }
+            ProcessState::STOPPED if self.started_at.is_none() => "Not started".to_string(),
+            ProcessState::EXITED | ProcessState::STOPPED => match self.exitstatus {
+                Some(code) => format!("exit status {}", code),
+                None => String::new(),
+            },
+            ProcessState::BACKOFF | ProcessState::FATAL => {
+                "Exited too quickly (process log may have details)".to_string()
+            }
+            ProcessState::STARTING | ProcessState::UNKNOWN => String::new(),
+        }
+    }
+
+    pub fn stdin(&self) -> Option<Arc<Mutex<ChildStdin>>> {
+        self.stdin.clone()
+    }
+
+    pub fn stdout(&self) -> Arc<Mutex<ProcessOutput>> {
+        Arc::clone(&self.stdout)
+    }
+
+    pub fn stderr(&self) -> Arc<Mutex<ProcessOutput>> {
+        Arc::clone(&self.stderr)
+    }
+
+    pub fn clear_logs(&self, program: &Program) -> Result<(), ExecError> {
+        self.stdout
+            .lock()
+            .unwrap()
+            .clear(program.stdout_logfile())?;
+        self.stderr
+            .lock()
+            .unwrap()
+            .clear(program.stderr_logfile())?;
+        Ok(())
+    }
+
+    pub fn start(&mut self, program: &Program, logger: &Logger) -> Result<(), ExecError> {
+        if self.state.is_running() {
+            return Err(ExecError::AlreadyStarted(self.name.clone()));
+        }
+        self.retries = 0;
+        self.spawn(program, logger)
+    }
+
+    pub fn stop(&mut self, program: &Program, logger: &Logger) -> Result<(), ExecError> {
+        match self.state {
+            ProcessState::BACKOFF => {
+                self.set_state(ProcessState::STOPPED, "stop requested");
+                Ok(())
+            }
+            ProcessState::STARTING | ProcessState::RUNNING => {
+                self.signal(program.stopsignal(), logger)?;
+                self.set_state(
+                    ProcessState::STOPPING,
+                    format!("sent {}", signal::name(program.stopsignal())),
+                );
+                self.stopping_since = Some(Instant::now());
+                self.killed = false;
+                Ok(())
+            }
+            ProcessState::STOPPING => Ok(()),
+            _ => Err(ExecError::NotRunning(self.name.clone())),
+        }
+    }
+
+    pub fn signal(&self, signum: i32, logger: &Logger) -> Result<(), ExecError> {
+        if !self.state.is_running() {
+            return Err(ExecError::NotRunning(self.name.clone()));
+        }
+        logger.debug(&format!(
+            "sending {} to {} (pid {})",
+            signal::name(signum),
+            self.name,
+            self.pid
+        ));
+        if unsafe { libc::kill(self.pid as libc::pid_t, signum) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+
+    fn spawn(&mut self, program: &Program, logger: &Logger) -> Result<(), ExecError> {
+        let mut argv = program.command().iter();
+        let mut command = Command::new(argv.next().ok_or(ExecError::InvalidArgs)?);
+        command
+            .args(argv)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // keep the children out of the shell's foreground group so that
+            // a Ctrl-C on the terminal is not delivered to them
+            .process_group(0);
+        if let Some(environment) = program.environment() {
+            for variable in environment.iter() {
+                if let Some((key, value)) = variable.split_once('=') {
+                    command.env(key, value);
+                }
+            }
+        }
+        if let Some(directory) = program.directory() {
+            command.current_dir(directory);
+        }
+        if let Some(umask) = program.umask() {
+            // umask is written in octal in the config file
+            let mask = libc::mode_t::from_str_radix(&umask.to_string(), 8).unwrap_or(0o022);
+            unsafe {
+                command.pre_exec(move || {
+                    libc::umask(mask);
+                    Ok(())
+                });
+            }
+        }
+
+        if self.started_at.is_some() {
+            self.restarts += 1;
+        }
+        self.started_at = Some(Instant::now());
+        self.exitstatus = None;
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                logger.error(&format!("spawnerr: {}: {}", self.name, e));
+                self.backoff(program, logger, format!("spawn error: {}", e));
+                return Err(ExecError::SpawnFailed(self.name.clone()));
+            }
+        };
+        self.stdout = Self::open_output(Stream::Stdout, program.stdout_logfile(), logger);
+        self.stderr = Self::open_output(Stream::Stderr, program.stderr_logfile(), logger);
+        self.subscribe_events(program);
+        if let Some(stdout) = child.stdout.take() {
+            ProcessOutput::capture(Arc::clone(&self.stdout), stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            ProcessOutput::capture(Arc::clone(&self.stderr), stderr);
+        }
+        self.stdin = child.stdin.take().map(|stdin| Arc::new(Mutex::new(stdin)));
+        self.pid = child.id();
+        self.last_pid = self.pid;
+        self.child = Some(child);
+        let reason = match self.state {
+            ProcessState::BACKOFF => "retrying after backoff",
+            ProcessState::EXITED => "autorestart",
+            _ => "start requested",
+        };
+        self.set_state(ProcessState::STARTING, reason);
+        logger.info(&format!("spawned: '{}' with pid {}", self.name, self.pid));
+        Ok(())
+    }
+
+    /// Subscribes to the new outputs before they are captured, so that
+    /// nothing the child writes first is missed.
+    fn subscribe_events(&mut self, program: &Program) {
+        self.protocol = program.listener().map(|_| {
+            let (tx, rx) = mpsc::channel();
+            self.stdout.lock().unwrap().subscribe(tx);
+            rx
+        });
+        self.log_events = None;
+        if program.stdout_events_enabled() || program.stderr_events_enabled() {
+            let (tx, rx) = mpsc::channel();
+            if program.stdout_events_enabled() {
+                self.stdout.lock().unwrap().subscribe(tx.clone());
+            }
+            if program.stderr_events_enabled() {
+                self.stderr.lock().unwrap().subscribe(tx);
+            }
+            self.log_events = Some(rx);
+        }
+    }
+
+    fn open_output(stream: Stream, path: &str, logger: &Logger) -> Arc<Mutex<ProcessOutput>> {
+        let output = ProcessOutput::new(stream, path).unwrap_or_else(|e| {
+            logger.error(&format!("cannot open logfile {}: {}", path, e));
+            ProcessOutput::without_logfile(stream)
+        });
+        Arc::new(Mutex::new(output))
+    }
+
+    fn backoff(&mut self, program: &Program, logger: &Logger, reason: String) {
+        self.failed_starts += 1;
+        if self.retries >= program.startretries() {
+            self.set_state(
+                ProcessState::FATAL,
+                format!("{}, too many start retries", reason),
+            );
+            logger.info(&format!(
+                "gave up: {} entered FATAL state, too many start retries too quickly",
+                self.name
+            ));
+            return;
+        }
+        self.retries += 1;
+        self.set_state(ProcessState::BACKOFF, reason);
+        self.backoff_until = Some(Instant::now() + Duration::from_secs(self.retries as u64));
+    }
+
+    fn try_wait(&mut self) -> Option<ExitStatus> {
+        let status = self.child.as_mut()?.try_wait().ok()??;
+        self.child = None;
+        self.stdin = None;
+        self.pid = 0;
+        self.exitstatus = status.code().or(status.signal().map(|s| -s));
+        Some(status)
+    }
+
+    fn is_expected(&self, program: &Program) -> bool {
+        self.exitstatus
+            .is_some_and(|code| program.exitcodes().contains(&code))
+    }
+
+    /// Advances the state machine; called periodically by the supervisor.
+    pub fn update(&mut self, program: &Program, logger: &Logger) {
+        let exited = self.try_wait().is_some();
+        let startsecs = Duration::from_secs(program.startsecs() as u64);
+        match self.state {
+            ProcessState::STARTING if exited => {
+                logger.info(&format!(
+                    "exited: {} (exit status {}; not expected)",
+                    self.name,
+                    self.exitstatus.unwrap_or_default()
+                ));
+                self.backoff(program, logger, "exited too quickly".to_string());
+            }
+            ProcessState::STARTING if self.started_at.is_some_and(|t| t.elapsed() >= startsecs) => {
+                self.set_state(
+                    ProcessState::RUNNING,
+                    format!("stayed up for {} seconds (startsecs)", program.startsecs()),
+                );
+                self.retries = 0;
+                logger.info(&format!(
+                    "success: {} entered RUNNING state, process has stayed up for > than {} seconds (startsecs)",
+                    self.name,
+                    program.startsecs()
+                ));
+            }
+            ProcessState::RUNNING if exited => {
+                let expected = self.is_expected(program);
+                self.set_state(
+                    ProcessState::EXITED,
+                    if expected {
+                        "expected exit"
+                    } else {
+                        "unexpected exit"
+                    },
+                );
+                logger.info(&format!(
+                    "exited: {} (exit status {}; {})",
+                    self.name,
+                    self.exitstatus.unwrap_or_default(),
+                    if expected { "expected" } else { "not expected" }
+                ));
+                let restart = match program.autorestart() {
+                    AutoRestart::True => true,
+                    AutoRestart::Unexpected => !expected,
+                    AutoRestart::False => false,
+                };
+                if restart {
+                    let _ = self.start(program, logger);
+                }
+            }
+            ProcessState::STOPPING if exited => {
+                let reason = match self.killed {
+                    true => "killed with SIGKILL after stopwaitsecs",
+                    false => "stopped",
+                };
+                self.set_state(ProcessState::STOPPED, reason);
+                logger.info(&format!(
+                    "stopped: {} (exit status {})",
+                    self.name,
+                    self.exitstatus.unwrap_or_default()
+                ));
+            }
+            ProcessState::STOPPING
+                if self.stopping_since.is_some_and(|t| {
+                    t.elapsed() >= Duration::from_secs(program.stopwaitsecs() as u64)
+                }) =>
+            {
+                logger.warn(&format!(
+                    "killing '{}' ({}) with SIGKILL",
+                    self.name, self.pid
+                ));
+                // only escalate once; the next ticks just wait for the reaper
+                self.stopping_since = None;
+                self.killed = true;
+                let _ = self.signal(libc::SIGKILL, logger);
+            }
+            ProcessState::BACKOFF if self.backoff_until.is_some_and(|t| Instant::now() >= t) => {
+                let _ = self.spawn(program, logger);
+            }
+            _ => {}
+        }
+    }
 }
@@ -1,9 +1,135 @@
 use std::collections::HashMap;
 
+use crate::config::logger::Logger;
+use crate::config::program::Program;
+use crate::errors::ExecError;
+use crate::exec::event::StateEvent;
+use crate::exec::event_listener::{Event, EventPool};
 use crate::exec::process::Process;
+use crate::exec::process_output::Stream;
 
 #[derive(Debug)]
 pub struct ProcessGroup {
     programname: String,
+    program: Program,
     processes: HashMap<String, Process>,
+    pool: Option<EventPool>, // set for event listeners
+}
+
+impl ProcessGroup {
+    pub fn new(program: &Program) -> Self {
+        let processes = program
+            .processnames()
+            .iter()
+            .map(|name| (name.clone(), Process::new(name.clone())))
+            .collect();
+        ProcessGroup {
+            programname: program.programname().clone(),
+            program: program.clone(),
+            processes,
+            pool: program
+                .listener()
+                .map(|listener| EventPool::new(program.programname(), listener)),
+        }
+    }
+
+    pub fn programname(&self) -> &String {
+        &self.programname
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn processes(&self) -> &HashMap<String, Process> {
+        &self.processes
+    }
+
+    pub fn get_process(&self, name: &str) -> Option<&Process> {
+        self.processes.get(name)
+    }
+
+    pub fn start(&mut self, name: &str, logger: &Logger) -> Result<(), ExecError> {
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| ExecError::ProcessNameNotFound(name.to_string()))?;
+        process.start(&self.program, logger)
+    }
+
+    pub fn stop(&mut self, name: &str, logger: &Logger) -> Result<(), ExecError> {
+        let process = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| ExecError::ProcessNameNotFound(name.to_string()))?;
+        process.stop(&self.program, logger)
+    }
+
+    pub fn autostart(&mut self, logger: &Logger) {
+        if !self.program.autostart() {
+            return;
+        }
+        for process in self.processes.values_mut() {
+            let _ = process.start(&self.program, logger);
+        }
+    }
+
+    /// Drains the state changes recorded by every process of the group.
+    pub fn take_events(&mut self) -> Vec<StateEvent> {
+        let mut events = Vec::new();
+        for (name, process) in self.processes.iter_mut() {
+            events.extend(
+                process
+                    .take_transitions()
+                    .into_iter()
+                    .map(|transition| StateEvent {
+                        group: self.programname.clone(),
+                        name: name.clone(),
+                        transition,
+                    }),
+            );
+        }
+        events
+    }
+
+    /// Drains the output of the processes with stdout or stderr events enabled.
+    pub fn take_log_output(&mut self) -> Vec<(String, u32, Stream, Vec<u8>)> {
+        let mut output = Vec::new();
+        for (name, process) in self.processes.iter() {
+            output.extend(
+                process
+                    .take_log_output()
+                    .into_iter()
+                    .map(|(stream, data)| (name.clone(), process.pid(), stream, data)),
+            );
+        }
+        output
+    }
+
+    /// Queues `event` for the listeners of the group, if it is one.
+    pub fn notify(&mut self, event: &Event, logger: &Logger) {
+        if let Some(pool) = self.pool.as_mut() {
+            pool.push(event, logger);
+        }
+    }
+
+    /// Sends the queued events to the listeners ready for one.
+    pub fn dispatch(&mut self, logger: &Logger) {
+        if let Some(pool) = self.pool.as_mut() {
+            pool.dispatch(&self.processes, logger);
+        }
+    }
+
+    pub fn take_failed_starts(&mut self) -> u64 {
+        self.processes
+            .values_mut()
+            .map(|process| process.take_failed_starts() as u64)
+            .sum()
+    }
+
+    pub fn update(&mut self, logger: &Logger) {
+        for process in self.processes.values_mut() {
+            process.update(&self.program, logger);
+        }
+    }
 }
@@ -1,8 +1,417 @@
 use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::config::logger::Logger;
+use crate::config::program::Program;
+use crate::config::runtimecontext::RuntimeContext;
+use crate::errors::ExecError;
+use crate::exec::event::StateEvent;
+use crate::exec::event_listener::Event;
+use crate::exec::hook;
+use crate::exec::notifier::Notifier;
+use crate::exec::process::{Process, ProcessState};
 use crate::exec::process_group::ProcessGroup;
+use crate::exec::selector::Selector;
+
+pub const TICK: Duration = Duration::from_millis(100);
+
+/// The periods of the TICK_* events, in seconds.
+const TICK_PERIODS: [u64; 3] = [5, 60, 3600];
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
 
 #[derive(Debug)]
 pub struct ProcessManager {
     process_groups: HashMap<String, ProcessGroup>,
+    logger: Logger,
+    subscribers: Vec<Sender<StateEvent>>,
+    spawn_failures: u64,
+    serial: u64,     // of the last event sent to the listeners
+    ticks: [u64; 3], // the last TICK_PERIODS boundaries crossed
+    notifier: Option<Notifier>,
+}
+
+impl ProcessManager {
+    pub fn new(rc: &RuntimeContext) -> Self {
+        let process_groups = rc
+            .config()
+            .programs()
+            .iter()
+            .map(|(name, program)| (name.clone(), ProcessGroup::new(program)))
+            .collect();
+        ProcessManager {
+            process_groups,
+            logger: rc.logger.clone(),
+            subscribers: Vec::new(),
+            spawn_failures: 0,
+            serial: 0,
+            ticks: TICK_PERIODS.map(|period| now() / period),
+            notifier: rc.config().notify().map(Notifier::new),
+        }
+    }
+
+    /// Splits `group:process` or a bare process name into its group and process parts.
+    fn resolve(&self, name: &str) -> Result<(String, String), ExecError> {
+        if let Some((group, process)) = name.split_once(':') {
+            return match self.process_groups.get(group) {
+                Some(g) if g.get_process(process).is_some() => {
+                    Ok((group.to_string(), process.to_string()))
+                }
+                _ => Err(ExecError::ProcessNameNotFound(name.to_string())),
+            };
+        }
+        self.process_groups
+            .values()
+            .find(|g| g.get_process(name).is_some())
+            .map(|g| (g.programname().clone(), name.to_string()))
+            .ok_or_else(|| ExecError::ProcessNameNotFound(name.to_string()))
+    }
+
+    /// Expands `all`, a glob or `re:` pattern, `group:process` or a bare
+    /// process name into sorted `(group, process)` pairs.
+    pub fn resolve_targets(&self, target: &str) -> Result<Vec<(String, String)>, ExecError> {
+        let Some(selector) = Selector::parse(target)? else {
+            return Ok(vec![self.resolve(target)?]);
+        };
+        let mut targets: Vec<(String, String)> = self
+            .process_groups
+            .values()
+            .flat_map(|g| {
+                g.processes()
+                    .keys()
+                    .map(|p| (g.programname().clone(), p.clone()))
+            })
+            .filter(|(group, process)| selector.matches(group, process))
+            .collect();
+        if targets.is_empty() {
+            return Err(ExecError::ProcessNameNotFound(target.to_string()));
+        }
+        targets.sort();
+        Ok(targets)
+    }
+
+    /// Resolves every target argument, without duplicates.
+    pub fn collect_targets(&self, args: &[String]) -> Result<Vec<(String, String)>, ExecError> {
+        let mut targets = Vec::new();
+        for target in args {
+            targets.extend(self.resolve_targets(target)?);
+        }
+        targets.sort();
+        targets.dedup();
+        Ok(targets)
+    }
+
+    /// Every name a target argument can take, for tab completion.
+    pub fn completion_names(&self) -> Vec<String> {
+        let mut names = vec!["all".to_string()];
+        for group in self.process_groups.values() {
+            names.push(format!("{}:*", group.programname()));
+            for process in group.processes().keys() {
+                names.push(format!("{}:{}", group.programname(), process));
+                names.push(process.clone());
+            }
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub fn get_program(&self, group: &str) -> Option<&Program> {
+        Some(self.process_groups.get(group)?.program())
+    }
+
+    pub fn get_process(&self, group: &str, process: &str) -> Option<&Process> {
+        self.process_groups.get(group)?.get_process(process)
+    }
+
+    pub fn find_process(&self, name: &str) -> Result<&Process, ExecError> {
+        let (group, process) = self.resolve(name)?;
+        Ok(self.process_groups[&group].get_process(&process).unwrap())
+    }
+
+    pub fn programs(&self) -> impl Iterator<Item = &Program> {
+        self.process_groups.values().map(|g| g.program())
+    }
+
+    /// Starts managing a new program, autostarting it if configured so.
+    pub fn add_group(&mut self, program: &Program) -> Result<(), ExecError> {
+        let name = program.programname();
+        if self.process_groups.contains_key(name) {
+            return Err(ExecError::AlreadyAdded(name.clone()));
+        }
+        let mut group = ProcessGroup::new(program);
+        group.autostart(&self.logger);
+        self.process_groups.insert(name.clone(), group);
+        self.publish();
+        Ok(())
+    }
+
+    /// Forgets a program whose processes have all stopped.
+    pub fn remove_group(&mut self, name: &str) -> Result<(), ExecError> {
+        let group = self
+            .process_groups
+            .get(name)
+            .ok_or_else(|| ExecError::ProcessNameNotFound(name.to_string()))?;
+        if group.processes().values().any(|p| !p.state().is_settled()) {
+            return Err(ExecError::StillRunning(name.to_string()));
+        }
+        self.process_groups.remove(name);
+        Ok(())
+    }
+
+    pub fn start(&mut self, group: &str, process: &str) -> Result<(), ExecError> {
+        let result = self
+            .process_groups
+            .get_mut(group)
+            .ok_or_else(|| ExecError::ProcessNameNotFound(group.to_string()))?
+            .start(process, &self.logger);
+        self.publish();
+        result
+    }
+
+    pub fn stop(&mut self, group: &str, process: &str) -> Result<(), ExecError> {
+        let result = self
+            .process_groups
+            .get_mut(group)
+            .ok_or_else(|| ExecError::ProcessNameNotFound(group.to_string()))?
+            .stop(process, &self.logger);
+        self.publish();
+        result
+    }
+
+    /// The pid of every process that has one, by `group:name`.
+    pub fn pids(&self) -> Vec<(String, u32)> {
+        self.process_groups
+            .values()
+            .flat_map(|g| {
+                g.processes()
+                    .iter()
+                    .filter(|(_, p)| p.pid() != 0)
+                    .map(|(name, p)| (format!("{}:{}", g.programname(), name), p.pid()))
+            })
+            .collect()
+    }
+
+    /// Starts that failed or exited too quickly since taskmasterd started.
+    pub fn spawn_failures(&self) -> u64 {
+        self.spawn_failures
+    }
+
+    /// Returns a channel receiving every state change from now on.
+    pub fn subscribe(&mut self) -> Receiver<StateEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Sends the recorded state changes to the subscribers, dropping the
+    /// ones that went away.
+    fn publish(&mut self) {
+        self.spawn_failures += self
+            .process_groups
+            .values_mut()
+            .map(|g| g.take_failed_starts())
+            .sum::<u64>();
+        let mut events: Vec<StateEvent> = self
+            .process_groups
+            .values_mut()
+            .flat_map(|g| g.take_events())
+            .collect();
+        events.sort_by(|a, b| (&a.group, &a.name).cmp(&(&b.group, &b.name)));
+        for event in events {
+            self.run_hook(&event);
+            if let Some(notifier) = self.notifier.as_mut()
+                && let Some(group) = self.process_groups.get(&event.group)
+            {
+                notifier.observe(&event, group.program(), &self.logger);
+            }
+            self.notify_state(&event);
+            self.subscribers
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+        let output: Vec<(String, _)> = self
+            .process_groups
+            .values_mut()
+            .flat_map(|g| {
+                let group = g.programname().clone();
+                g.take_log_output()
+                    .into_iter()
+                    .map(move |o| (group.clone(), o))
+            })
+            .collect();
+        for (group, (name, pid, stream, data)) in output {
+            let serial = self.next_serial();
+            self.notify(Event::process_log(
+                serial,
+                (&group, &name),
+                pid,
+                stream,
+                &data,
+            ));
+        }
+        let now = now();
+        for (i, period) in TICK_PERIODS.into_iter().enumerate() {
+            if now / period != self.ticks[i] {
+                self.ticks[i] = now / period;
+                let serial = self.next_serial();
+                self.notify(Event::tick(serial, period, now));
+            }
+        }
+        for group in self.process_groups.values_mut() {
+            group.dispatch(&self.logger);
+        }
+    }
+
+    fn next_serial(&mut self) -> u64 {
+        self.serial += 1;
+        self.serial
+    }
+
+    /// Queues `event` for every event listener subscribed to it.
+    fn notify(&mut self, event: Event) {
+        for group in self.process_groups.values_mut() {
+            group.notify(&event, &self.logger);
+        }
+    }
+
+    /// Runs the hook the program of the process has for its new state.
+    fn run_hook(&self, event: &StateEvent) {
+        let Some(program) = self.get_program(&event.group) else {
+            return;
+        };
+        if let Some((key, command)) = hook::for_state(program, event.transition.to) {
+            let timeout = Duration::from_secs(program.hook_timeout() as u64);
+            hook::run(key, command, event, timeout, self.logger.clone());
+        }
+    }
+
+    fn notify_state(&mut self, event: &StateEvent) {
+        let Some(group) = self.process_groups.get(&event.group) else {
+            return;
+        };
+        let tries = group
+            .get_process(&event.name)
+            .map_or(0, |process| process.retries());
+        let expected = event
+            .transition
+            .exitstatus
+            .is_some_and(|code| group.program().exitcodes().contains(&code));
+        let serial = self.next_serial();
+        self.notify(Event::process_state(serial, event, tries, expected));
+    }
+
+    /// Tells the event listeners that taskmasterd is `RUNNING` or `STOPPING`.
+    fn notify_supervisor(&mut self, state: &str) {
+        let serial = self.next_serial();
+        self.notify(Event::supervisor_state(serial, state));
+        for group in self.process_groups.values_mut() {
+            group.dispatch(&self.logger);
+        }
+    }
+
+    /// Groups the processes by program priority, highest first, which is the
+    /// order they are stopped in.
+    fn stop_order(&self) -> Vec<Vec<(String, String)>> {
+        let mut groups: Vec<&ProcessGroup> = self.process_groups.values().collect();
+        groups.sort_by_key(|g| (std::cmp::Reverse(g.program().priority()), g.programname()));
+        let mut batches: Vec<(i32, Vec<(String, String)>)> = Vec::new();
+        for group in groups {
+            let priority = group.program().priority();
+            if batches.last().is_none_or(|(p, _)| *p != priority) {
+                batches.push((priority, Vec::new()));
+            }
+            let batch = &mut batches.last_mut().unwrap().1;
+            let mut names: Vec<&String> = group.processes().keys().collect();
+            names.sort();
+            for name in names {
+                batch.push((group.programname().clone(), name.clone()));
+            }
+        }
+        batches.into_iter().map(|(_, batch)| batch).collect()
+    }
+
+    /// Stops every process one priority level at a time, waiting for each
+    /// level to exit (or be killed after stopwaitsecs) before the next one.
+    /// Returns the processes that had to be SIGKILLed.
+    pub fn shutdown(manager: &Arc<Mutex<ProcessManager>>) -> Vec<(String, String)> {
+        let mut killed = Vec::new();
+        let batches = {
+            let mut manager = manager.lock().unwrap();
+            manager.notify_supervisor("STOPPING");
+            manager.stop_order()
+        };
+        for batch in batches {
+            let stopping: Vec<(String, String)> = {
+                let mut manager = manager.lock().unwrap();
+                batch
+                    .into_iter()
+                    .filter(|(group, name)| manager.stop(group, name).is_ok())
+                    .collect()
+            };
+            loop {
+                let manager = manager.lock().unwrap();
+                // a process whose group was removed meanwhile has stopped
+                let pending = stopping.iter().any(|(group, name)| {
+                    manager
+                        .get_process(group, name)
+                        .is_some_and(|process| process.state() == ProcessState::STOPPING)
+                });
+                if !pending {
+                    killed.extend(
+                        stopping
+                            .iter()
+                            .filter(|(group, name)| {
+                                manager
+                                    .get_process(group, name)
+                                    .is_some_and(Process::was_killed)
+                            })
+                            .cloned(),
+                    );
+                    break;
+                }
+                drop(manager);
+                thread::sleep(TICK);
+            }
+        }
+        killed
+    }
+
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Starts the autostart programs, lowest priority first.
+    pub fn autostart(&mut self) {
+        let mut groups: Vec<&mut ProcessGroup> = self.process_groups.values_mut().collect();
+        groups.sort_by_key(|g| (g.program().priority(), g.programname().clone()));
+        for group in groups {
+            group.autostart(&self.logger);
+        }
+        self.publish();
+        self.notify_supervisor("RUNNING");
+    }
+
+    pub fn update(&mut self) {
+        for group in self.process_groups.values_mut() {
+            group.update(&self.logger);
+        }
+        self.publish();
+    }
+
+    /// Starts the supervisor thread which reaps and restarts the children.
+    pub fn supervise(manager: Arc<Mutex<ProcessManager>>) -> JoinHandle<()> {
+        thread::spawn(move || {
+            loop {
+                manager.lock().unwrap().update();
+                thread::sleep(TICK);
+            }
+        })
+    }
 }
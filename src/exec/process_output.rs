@@ -0,0 +1,84 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug)]
+pub struct ProcessOutput {
+    stream: Stream,
+    logfile: Option<File>,
+    listeners: Vec<Sender<(Stream, Vec<u8>)>>,
+}
+
+impl ProcessOutput {
+    pub fn new(stream: Stream, path: &str) -> std::io::Result<Self> {
+        let logfile = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(ProcessOutput {
+            stream,
+            logfile: Some(logfile),
+            listeners: Vec::new(),
+        })
+    }
+
+    pub fn without_logfile(stream: Stream) -> Self {
+        ProcessOutput {
+            stream,
+            logfile: None,
+            listeners: Vec::new(),
+        }
+    }
+
+    pub fn subscribe(&mut self, listener: Sender<(Stream, Vec<u8>)>) {
+        self.listeners.push(listener);
+    }
+
+    /// Empties the logfile; the child keeps appending to it from offset 0.
+    /// `path` is truncated directly when the process has not opened it yet.
+    pub fn clear(&mut self, path: &str) -> std::io::Result<()> {
+        match self.logfile.as_ref() {
+            Some(file) => file.set_len(0),
+            None => match OpenOptions::new().write(true).open(path) {
+                Ok(file) => file.set_len(0),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    fn write(&mut self, buf: &[u8]) {
+        if let Some(file) = self.logfile.as_mut() {
+            let _ = file.write_all(buf);
+        }
+        let stream = self.stream;
+        self.listeners
+            .retain(|listener| listener.send((stream, buf.to_vec())).is_ok());
+    }
+
+    fn close(&mut self) {
+        self.listeners.clear();
+    }
+
+    /// Copies everything read from `pipe` into the output until the child closes it.
+    pub fn capture<R: Read + Send + 'static>(output: Arc<Mutex<ProcessOutput>>, mut pipe: R) {
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                match pipe.read(&mut buf) {
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => output.lock().unwrap().write(&buf[..n]),
+                }
+            }
+            output.lock().unwrap().close();
+        });
+    }
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::time::Duration;
+
+/// Resource usage of a running process, read from /proc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    /// User plus system CPU time consumed so far.
+    pub cpu: Duration,
+    /// Resident set size in bytes.
+    pub rss: u64,
+}
+
+pub fn usage(pid: u32) -> Option<Usage> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (ticks, pages) = parse_stat(&stat)?;
+    let hz = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if hz <= 0 || page_size <= 0 {
+        return None;
+    }
+    Some(Usage {
+        cpu: Duration::from_secs_f64(ticks as f64 / hz as f64),
+        rss: pages * page_size as u64,
+    })
+}
+
+/// Extracts utime + stime (in clock ticks) and rss (in pages) from the
+/// contents of /proc/<pid>/stat.
+fn parse_stat(stat: &str) -> Option<(u64, u64)> {
+    // the command name is in parentheses and may itself contain spaces or
+    // parentheses, so the fixed fields are counted from the last ')'
+    let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    // fields[0] is field 3 (state) of proc(5)
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let rss: u64 = fields.get(21)?.parse().ok()?;
+    Some((utime + stime, rss))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod parse_stat_tests {
+        use super::*;
+
+        const STAT: &str = "4242 (my (odd) cmd) S 1 4242 4242 0 -1 4194560 120 0 0 0 \
+                            25 17 0 0 20 0 1 0 881 2306048 384 18446744073709551615";
+
+        #[test]
+        fn test_parse_stat_valid() {
+            assert_eq!(parse_stat(STAT), Some((42, 384)));
+        }
+
+        #[test]
+        fn test_parse_stat_truncated() {
+            assert_eq!(parse_stat("4242 (cat) S 1 4242"), None);
+        }
+
+        #[test]
+        fn test_parse_stat_garbage() {
+            assert_eq!(parse_stat("not a stat line"), None);
+        }
+    }
+}
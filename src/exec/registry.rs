@@ -0,0 +1,200 @@
+use std::cell::Cell;
+
+use crate::commandline::CommandLine;
+use crate::control::client::Client;
+use crate::errors::ExecError;
+
+/// What an argument names, used to pick tab completion candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    Process,
+    Signal,
+    Command,
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Arg {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub required: bool,
+    pub repeated: bool,
+}
+
+impl Arg {
+    pub const fn required(name: &'static str, kind: ArgKind) -> Self {
+        Arg {
+            name,
+            kind,
+            required: true,
+            repeated: false,
+        }
+    }
+
+    pub const fn optional(name: &'static str, kind: ArgKind) -> Self {
+        Arg {
+            name,
+            kind,
+            required: false,
+            repeated: false,
+        }
+    }
+
+    /// Lets the argument be given any number of times; only valid for the last one.
+    pub const fn repeated(self) -> Self {
+        Arg {
+            repeated: true,
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Display for Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let repeated = if self.repeated { "..." } else { "" };
+        if self.required {
+            write!(f, "<{}>{}", self.name, repeated)
+        } else {
+            write!(f, "[<{}>{}]", self.name, repeated)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+/// State kept by the shell across commands.
+#[derive(Debug, Default)]
+pub struct Session {
+    pub format: Cell<Format>,
+    pub interactive: bool,
+    pub finished: Cell<bool>,
+}
+
+impl Session {
+    pub fn new(format: Format, interactive: bool) -> Self {
+        Session {
+            format: Cell::new(format),
+            interactive,
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn json(&self) -> bool {
+        self.format.get() == Format::Json
+    }
+
+    /// Whether a command asked the shell to exit after it.
+    pub fn finished(&self) -> bool {
+        self.finished.get()
+    }
+}
+
+pub struct Context<'a> {
+    pub client: &'a Client,
+    pub registry: &'a Registry,
+    pub session: &'a Session,
+}
+
+pub trait Command: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn args(&self) -> &'static [Arg] {
+        &[]
+    }
+
+    fn help(&self) -> &'static str;
+
+    fn run(&self, args: &[String], ctx: &Context) -> Result<(), ExecError>;
+
+    fn usage(&self) -> String {
+        let mut usage = self.name().to_string();
+        for arg in self.args() {
+            usage.push_str(&format!(" {}", arg));
+        }
+        usage
+    }
+
+    /// The kind of the argument at `index`, following a repeated last argument.
+    fn arg_kind(&self, index: usize) -> Option<ArgKind> {
+        let args = self.args();
+        match args.get(index) {
+            Some(arg) => Some(arg.kind),
+            None => args.last().filter(|arg| arg.repeated).map(|arg| arg.kind),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    commands: Vec<Box<dyn Command>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, command: Box<dyn Command>) -> &mut Self {
+        self.commands.push(command);
+        self.commands.sort_by_key(|c| c.name());
+        self
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = &dyn Command> {
+        self.commands.iter().map(|c| c.as_ref())
+    }
+
+    pub fn find(&self, name: &str) -> Option<&dyn Command> {
+        self.commands()
+            .find(|c| c.name() == name || c.aliases().contains(&name))
+    }
+
+    /// Every name and alias a command can be invoked by.
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self
+            .commands()
+            .flat_map(|c| std::iter::once(c.name()).chain(c.aliases().iter().copied()))
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn dispatch(
+        &self,
+        line: &CommandLine,
+        client: &Client,
+        session: &Session,
+    ) -> Result<(), ExecError> {
+        let command = self
+            .find(line.command())
+            .ok_or_else(|| ExecError::UnknownCommand(line.command().clone()))?;
+        Self::validate(command, line.args())?;
+        let ctx = Context {
+            client,
+            registry: self,
+            session,
+        };
+        command.run(line.args(), &ctx)
+    }
+
+    fn validate(command: &dyn Command, args: &[String]) -> Result<(), ExecError> {
+        let schema = command.args();
+        let required = schema.iter().filter(|arg| arg.required).count();
+        let unbounded = schema.last().is_some_and(|arg| arg.repeated);
+        if args.len() < required || (!unbounded && args.len() > schema.len()) {
+            return Err(ExecError::Usage(command.usage()));
+        }
+        Ok(())
+    }
+}
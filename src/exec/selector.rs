@@ -0,0 +1,146 @@
+use regex::Regex;
+
+use crate::errors::ExecError;
+
+/// A process target given as a pattern rather than a single name.
+///
+/// Globs (`*`, `?`) containing a `:` are matched against `group:process`,
+/// other globs against the process name alone. `re:` patterns are matched
+/// against both forms.
+#[derive(Debug)]
+pub enum Selector {
+    All,
+    Glob(String),
+    Regex(Regex),
+}
+
+impl Selector {
+    const REGEX_PREFIX: &str = "re:";
+
+    /// Returns `None` for a plain name.
+    pub fn parse(target: &str) -> Result<Option<Self>, ExecError> {
+        if target == "all" {
+            Ok(Some(Selector::All))
+        } else if let Some(pattern) = target.strip_prefix(Self::REGEX_PREFIX) {
+            Regex::new(pattern)
+                .map(|re| Some(Selector::Regex(re)))
+                .map_err(|e| ExecError::InvalidPattern(format!("{}: {}", target, e)))
+        } else if target.contains(['*', '?']) {
+            Ok(Some(Selector::Glob(target.to_string())))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Whether `target` may name more than one process.
+    pub fn is_pattern(target: &str) -> bool {
+        target == "all" || target.starts_with(Self::REGEX_PREFIX) || target.contains(['*', '?'])
+    }
+
+    pub fn matches(&self, group: &str, process: &str) -> bool {
+        let fullname = format!("{}:{}", group, process);
+        match self {
+            Selector::All => true,
+            Selector::Glob(pattern) if pattern.contains(':') => glob_match(pattern, &fullname),
+            Selector::Glob(pattern) => glob_match(pattern, process),
+            Selector::Regex(re) => re.is_match(process) || re.is_match(&fullname),
+        }
+    }
+}
+
+/// Matches `text` against a shell-style pattern where `*` is any run of
+/// characters and `?` any single character.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // position of the last `*` seen and of the text it currently absorbs up to
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, absorbed)) => {
+                    p = star + 1;
+                    t = absorbed + 1;
+                    backtrack = Some((star, absorbed + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod glob_match_tests {
+        use super::*;
+
+        #[test]
+        fn test_glob_match_literal() {
+            assert!(glob_match("web", "web"));
+            assert!(!glob_match("web", "web1"));
+        }
+
+        #[test]
+        fn test_glob_match_star() {
+            assert!(glob_match("web-*", "web-1"));
+            assert!(glob_match("web-*", "web-"));
+            assert!(!glob_match("web-*", "api-1"));
+            assert!(glob_match("*:worker*", "jobs:worker12"));
+            assert!(!glob_match("*:worker*", "jobs:web"));
+            assert!(glob_match("*a*b*", "xxaybzb"));
+        }
+
+        #[test]
+        fn test_glob_match_question_mark() {
+            assert!(glob_match("api?", "api1"));
+            assert!(!glob_match("api?", "api12"));
+        }
+    }
+
+    mod selector_tests {
+        use super::*;
+
+        #[test]
+        fn test_selector_plain_name() {
+            assert!(Selector::parse("web:web1").unwrap().is_none());
+        }
+
+        #[test]
+        fn test_selector_glob_without_group() {
+            let selector = Selector::parse("web*").unwrap().unwrap();
+            assert!(selector.matches("frontend", "web3"));
+            assert!(!selector.matches("web", "api"));
+        }
+
+        #[test]
+        fn test_selector_glob_with_group() {
+            let selector = Selector::parse("jobs:*").unwrap().unwrap();
+            assert!(selector.matches("jobs", "worker0"));
+            assert!(!selector.matches("web", "jobs"));
+        }
+
+        #[test]
+        fn test_selector_regex() {
+            let selector = Selector::parse(r"re:^api\d+$").unwrap().unwrap();
+            assert!(selector.matches("api", "api12"));
+            assert!(!selector.matches("api", "api"));
+        }
+
+        #[test]
+        fn test_selector_invalid_regex() {
+            assert!(Selector::parse("re:(").is_err());
+        }
+    }
+}
@@ -0,0 +1,93 @@
+const SIGNALS: [(&str, i32); 29] = [
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("URG", libc::SIGURG),
+    ("XCPU", libc::SIGXCPU),
+    ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM),
+    ("PROF", libc::SIGPROF),
+    ("WINCH", libc::SIGWINCH),
+    ("IO", libc::SIGIO),
+    ("SYS", libc::SIGSYS),
+];
+
+/// Every signal name, without the SIG prefix.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    SIGNALS.iter().map(|(name, _)| *name)
+}
+
+/// Parses a signal given by name (`HUP`, `SIGHUP`, case-insensitive) or by number.
+pub fn from_str(s: &str) -> Option<i32> {
+    if let Ok(signum) = s.parse::<i32>() {
+        return (1..=libc::SIGRTMAX()).contains(&signum).then_some(signum);
+    }
+    let upper = s.to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    SIGNALS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, signum)| *signum)
+}
+
+pub fn name(signum: i32) -> String {
+    SIGNALS
+        .iter()
+        .find(|(_, s)| *s == signum)
+        .map(|(n, _)| format!("SIG{}", n))
+        .unwrap_or_else(|| signum.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str_name() {
+        assert_eq!(from_str("HUP"), Some(libc::SIGHUP));
+        assert_eq!(from_str("usr1"), Some(libc::SIGUSR1));
+    }
+
+    #[test]
+    fn test_from_str_sig_prefix() {
+        assert_eq!(from_str("SIGTERM"), Some(libc::SIGTERM));
+        assert_eq!(from_str("sigusr2"), Some(libc::SIGUSR2));
+    }
+
+    #[test]
+    fn test_from_str_number() {
+        assert_eq!(from_str("9"), Some(libc::SIGKILL));
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        assert_eq!(from_str("FOO"), None);
+        assert_eq!(from_str("0"), None);
+        assert_eq!(from_str("-1"), None);
+        assert_eq!(from_str("SIG"), None);
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(name(libc::SIGHUP), "SIGHUP");
+        assert_eq!(name(libc::SIGRTMIN()), libc::SIGRTMIN().to_string());
+    }
+}
@@ -0,0 +1,6 @@
+pub mod commandline;
+pub mod config;
+pub mod control;
+pub mod daemonize;
+pub mod errors;
+pub mod exec;
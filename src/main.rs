@@ -1,38 +1,213 @@
-mod commandline;
-mod config;
-mod errors;
-mod exec;
-
-use commandline::CommandLine;
-use config::{
-    adapter::Adapter,
-    logger::{LogLevel, Logger},
-    runtimecontext::RuntimeContext,
-};
-use errors::CommandLineError::EmptyCommand;
 use std::env;
+use std::fs;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
-use exec::command_loop::command_loop;
+use signal_hook::consts::signal::{SIGINT, SIGQUIT, SIGTERM};
+use signal_hook::iterator::Signals;
+
+use taskmaster::config::config::Config;
+use taskmaster::config::{adapter::Adapter, runtimecontext::RuntimeContext};
+use taskmaster::control::auth::Credentials;
+use taskmaster::control::server;
+use taskmaster::daemonize::{self, Children, Pidfile};
+use taskmaster::exec::process_manager::ProcessManager;
+use taskmaster::exec::signal;
+
+/// How long the processes a crashed taskmasterd left behind get to exit,
+/// as with the default stopwaitsecs.
+const ORPHAN_STOP_TIMEOUT: Duration = Duration::from_secs(10);
 
 fn usage(s: &str) {
-    eprintln!("Usage: {} <config_file_path>", s);
+    eprintln!("Usage: {} [[-c] <config_file_path>]", s);
     std::process::exit(1);
 }
 
+fn parse_args(args: &[String]) -> Option<Option<String>> {
+    let mut file_path = None;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-c" if file_path.is_none() => file_path = Some(iter.next()?.clone()),
+            _ if file_path.is_none() && !arg.starts_with('-') => file_path = Some(arg.clone()),
+            _ => return None,
+        }
+    }
+    Some(file_path)
+}
+
 fn main() {
     let args = env::args().collect::<Vec<_>>();
-    if args.len() != 1 && args.len() != 2 {
+    let Some(file_path) = parse_args(&args) else {
         usage(&args[0]);
-    }
-    let file_path = if args.len() == 2 {
-        Some(&args[1])
-    } else {
-        None
+        return;
     };
     let mut runtime_context = RuntimeContext::default();
-    if let Err(e) = Adapter::parse_config(&mut runtime_context, file_path) {
+    // a daemon missing part of its configuration would fail unnoticed
+    if let Err(e) = Adapter::parse_config(&mut runtime_context, file_path.as_ref()) {
         eprintln!("Error parsing config: {}", e);
+        std::process::exit(1);
+    }
+    // reloads read the file again, possibly from another directory
+    let file_path = file_path
+        .or_else(|| Config::find_config().ok())
+        .map(|path| {
+            fs::canonicalize(&path).map_or(path, |path| path.to_string_lossy().into_owned())
+        });
+    let taskmasterd = runtime_context.config().taskmasterd().clone();
+    unsafe { libc::umask(taskmasterd.umask() as libc::mode_t) };
+    if !taskmasterd.nodaemon()
+        && let Some(directory) = taskmasterd.directory()
+        && let Err(e) = env::set_current_dir(directory)
+    {
+        eprintln!(
+            "taskmasterd: cannot change directory to {}: {}",
+            directory, e
+        );
+        std::process::exit(1);
+    }
+    let mut pidfile = match Pidfile::lock(taskmasterd.pidfile()) {
+        Ok(pidfile) => pidfile,
+        Err(e) => {
+            eprintln!("taskmasterd: {}: {}", taskmasterd.pidfile(), e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(pid) = pidfile.stale_pid() {
+        runtime_context.logger.warn(&format!(
+            "{} was left behind by pid {}, which did not exit cleanly",
+            pidfile.path(),
+            pid
+        ));
+    }
+    // its children survived it, and would otherwise run twice
+    let children = Children::new(pidfile.path());
+    let orphans = children.alive();
+    if !orphans.is_empty() {
+        let names: Vec<String> = orphans
+            .iter()
+            .map(|(name, pid)| format!("{} (pid {})", name, pid))
+            .collect();
+        runtime_context.logger.warn(&format!(
+            "stopping what a previous taskmasterd left running: {}",
+            names.join(", ")
+        ));
+        for (name, pid) in daemonize::stop_orphans(&orphans, ORPHAN_STOP_TIMEOUT) {
+            runtime_context.logger.warn(&format!(
+                "{} (pid {}) did not stop in time, killed",
+                name, pid
+            ));
+        }
+    }
+    children.remove();
+    let socket = runtime_context.config().unix_http_server().clone();
+    match server::remove_stale_socket(socket.file()) {
+        Ok(true) => runtime_context
+            .logger
+            .warn(&format!("removed stale control socket {}", socket.file())),
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!("taskmasterd: cannot listen on {}: {}", socket.file(), e);
+            pidfile.remove();
+            std::process::exit(1);
+        }
+    }
+    let listener = match server::bind(&socket) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("taskmasterd: cannot listen on {}: {}", socket.file(), e);
+            pidfile.remove();
+            std::process::exit(1);
+        }
+    };
+    let inet = runtime_context.config().inet_http_server().clone();
+    let inet_listener = match inet.port().map(|_| server::bind_inet(&inet)) {
+        Some(Ok(listener)) => Some(listener),
+        Some(Err(e)) => {
+            let (host, port) = inet.port().unwrap();
+            eprintln!("taskmasterd: cannot listen on {}:{}: {}", host, port, e);
+            let _ = fs::remove_file(socket.file());
+            pidfile.remove();
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    // before any thread is started: only the forking one would survive
+    if !taskmasterd.nodaemon()
+        && let Err(e) = daemonize::daemonize(taskmasterd.logfile())
+    {
+        eprintln!("taskmasterd: cannot daemonize: {}", e);
+        let _ = fs::remove_file(socket.file());
+        pidfile.remove();
+        std::process::exit(1);
     }
     runtime_context.logger.info("starting taskmasterd");
-    command_loop(runtime_context);
+    if let Err(e) = pidfile.write_pid() {
+        runtime_context
+            .logger
+            .error(&format!("cannot write pidfile {}: {}", pidfile.path(), e));
+        let _ = fs::remove_file(socket.file());
+        pidfile.remove();
+        std::process::exit(1);
+    }
+    let manager = Arc::new(Mutex::new(ProcessManager::new(&runtime_context)));
+    let events = manager.lock().unwrap().subscribe();
+    {
+        let manager = Arc::clone(&manager);
+        let children = children.clone();
+        let logger = runtime_context.logger.clone();
+        thread::spawn(move || {
+            for _ in events {
+                let pids = manager.lock().unwrap().pids();
+                if let Err(e) = children.write(&pids) {
+                    logger.error(&format!("cannot record the children pids: {}", e));
+                }
+            }
+        });
+    }
+    manager.lock().unwrap().autostart();
+    ProcessManager::supervise(Arc::clone(&manager));
+
+    let (exit_tx, exit_rx) = mpsc::channel();
+    let mut signals = match Signals::new([SIGINT, SIGTERM, SIGQUIT]) {
+        Ok(signals) => signals,
+        Err(e) => {
+            eprintln!("taskmasterd: cannot handle signals: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let logger = runtime_context.logger.clone();
+    let on_signal = exit_tx.clone();
+    thread::spawn(move || {
+        if let Some(signum) = signals.forever().next() {
+            logger.info(&format!("received {}, shutting down", signal::name(signum)));
+            let _ = on_signal.send(());
+        }
+    });
+    let daemon = match server::Daemon::new(Arc::clone(&manager), exit_tx, file_path) {
+        Ok(daemon) => daemon,
+        Err(e) => {
+            eprintln!("taskmasterd: cannot start the HTTP runtime: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let daemon = Arc::new(daemon);
+    if let Some(inet_listener) = inet_listener {
+        server::serve_inet(
+            inet_listener,
+            Arc::clone(&daemon),
+            Credentials::from_config(&inet),
+        );
+    }
+    server::serve(listener, daemon);
+
+    let _ = exit_rx.recv();
+    // never leave orphans behind, however taskmasterd was asked to exit
+    ProcessManager::shutdown(&manager);
+    let _ = fs::remove_file(socket.file());
+    children.remove();
+    pidfile.remove();
+    runtime_context.logger.info("taskmasterd stopped");
 }